@@ -42,7 +42,7 @@ pub fn generate_hash_onion(layers: usize, center: Vec<u8>) -> Vec<Vec<u8>> {
 }
 
 pub fn setup(
-    balances: HashMap<ellipticoin::Address, Vec<(Token, u64)>>,
+    balances: HashMap<ellipticoin::Address, Vec<(Token, u128)>>,
     state: &mut HashMap<Vec<u8>, Vec<u8>>,
 ) -> TestAPI {
     env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
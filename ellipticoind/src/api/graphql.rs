@@ -1,6 +1,6 @@
 extern crate juniper;
-use crate::api::{mutations::Mutations, query_root::QueryRoot};
-use juniper::{graphql_value, EmptySubscription, Variables};
+use crate::api::{mutations::Mutations, query_root::QueryRoot, subscriptions::Subscriptions};
+use juniper::{graphql_value, Variables};
 use serde_json::json;
 use std::fmt;
 use tide::{http::StatusCode, Body, Request, Response};
@@ -11,8 +11,8 @@ impl juniper::Context for Context {}
 #[derive(Debug)]
 pub struct Error(pub String);
 
-impl juniper::IntoFieldError for Error {
-    fn into_field_error(self) -> juniper::FieldError {
+impl<S: juniper::ScalarValue> juniper::IntoFieldError<S> for Error {
+    fn into_field_error(self) -> juniper::FieldError<S> {
         juniper::FieldError::new(
             self.to_string(),
             graphql_value!({
@@ -28,7 +28,7 @@ impl fmt::Display for Error {
     }
 }
 
-pub type Schema = juniper::RootNode<'static, QueryRoot, Mutations, EmptySubscription<Context>>;
+pub type Schema = juniper::RootNode<'static, QueryRoot, Mutations, Subscriptions>;
 pub async fn handle_graphql(mut request: Request<()>) -> tide::Result {
     let ctx = Context {};
 
@@ -52,7 +52,7 @@ pub async fn handle_graphql(mut request: Request<()>) -> tide::Result {
     let (res, errors) = juniper::execute(
         &query,
         None,
-        &Schema::new(QueryRoot, Mutations, EmptySubscription::new()),
+        &Schema::new(QueryRoot, Mutations, Subscriptions),
         &variables,
         &ctx,
     )
@@ -1,16 +1,96 @@
-use crate::api::graphql::Error;
+use crate::{api::graphql::Error, error::SENDER_IS_NOT_SIGNER, transaction::TransactionRequest};
 use ed25519_zebra::VerificationKey;
 use serde::de::DeserializeOwned;
 use std::convert::TryFrom;
 
 pub fn validate_signature<D: DeserializeOwned>(cose_bytes: &[u8]) -> Result<D, Error> {
+    validate_signature_with_signer(cose_bytes).map(|(payload, _signer)| payload)
+}
+
+pub fn validate_transaction(cose_bytes: &[u8]) -> Result<TransactionRequest, Error> {
+    let (transaction_request, signer): (TransactionRequest, [u8; 32]) =
+        validate_signature_with_signer(cose_bytes)?;
+    if transaction_request.sender != signer {
+        return Err(Error(SENDER_IS_NOT_SIGNER.message.clone()));
+    }
+    Ok(transaction_request)
+}
+
+pub fn validate_signature_with_signer<D: DeserializeOwned>(
+    cose_bytes: &[u8],
+) -> Result<(D, [u8; 32]), Error> {
     let sign1: serde_cose::Sign1 = serde_cbor::from_slice(cose_bytes)
         .map_err(|_| Error("invalid COSE message".to_string()))?;
+    let signer = <[u8; 32]>::try_from(&sign1.kid()[..])
+        .map_err(|_| Error("invalid signature".to_string()))?;
     let key = serde_cose::Key::from(
-        VerificationKey::try_from(<[u8; 32]>::try_from(&sign1.kid()[..]).unwrap())
-            .map_err(|_| Error("invalid signature".to_string()))?,
+        VerificationKey::try_from(signer).map_err(|_| Error("invalid signature".to_string()))?,
     );
     key.verify(&sign1)
         .map_err(|_| Error("invalid signature".to_string()))?;
-    serde_cbor::from_slice(&sign1.payload).map_err(|_| Error("invalid CBOR payload".to_string()))
+    let payload = serde_cbor::from_slice(&sign1.payload)
+        .map_err(|_| Error("invalid CBOR payload".to_string()))?;
+    Ok((payload, signer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_zebra::SigningKey;
+    use ellipticoin_test_framework::constants::actors::{
+        ALICE, ALICES_PRIVATE_KEY, BOB, BOBS_PRIVATE_KEY,
+    };
+    use serde_cose::Sign1;
+
+    fn sign(
+        transaction_request: &TransactionRequest,
+        private_key: [u8; 64],
+        kid: [u8; 32],
+    ) -> Vec<u8> {
+        let mut sign1 = Sign1::new(transaction_request, kid.to_vec());
+        sign1.sign(SigningKey::from(
+            <[u8; 32]>::try_from(&private_key[..32]).unwrap(),
+        ));
+        serde_cbor::to_vec(&sign1).unwrap()
+    }
+
+    fn transaction_request(sender: [u8; 32]) -> TransactionRequest {
+        TransactionRequest {
+            network_id: 0,
+            contract: "Token".to_string(),
+            sender,
+            nonce: 1,
+            function: "transfer".to_string(),
+            arguments: vec![],
+        }
+    }
+
+    #[test]
+    fn test_validate_transaction() {
+        let transaction_request = transaction_request(*ALICE);
+        assert_eq!(
+            validate_transaction(&sign(&transaction_request, ALICES_PRIVATE_KEY, *ALICE)).unwrap(),
+            transaction_request
+        );
+    }
+
+    #[test]
+    fn test_validate_transaction_spoofed_sender() {
+        assert_eq!(
+            validate_transaction(&sign(&transaction_request(*ALICE), BOBS_PRIVATE_KEY, *BOB))
+                .unwrap_err()
+                .0,
+            SENDER_IS_NOT_SIGNER.message
+        );
+    }
+
+    #[test]
+    fn test_validate_transaction_spoofed_kid() {
+        assert!(validate_transaction(&sign(
+            &transaction_request(*ALICE),
+            BOBS_PRIVATE_KEY,
+            *ALICE
+        ))
+        .is_err());
+    }
 }
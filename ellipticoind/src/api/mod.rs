@@ -2,13 +2,15 @@ pub use futures::stream::StreamExt;
 pub mod app;
 mod blocks;
 pub mod graphql;
-mod helpers;
+pub mod helpers;
 mod middleware;
 mod mutations;
 mod query_root;
 mod routes;
+mod subscriptions;
 mod types;
 pub mod views;
+pub mod websocket;
 pub struct API {
     pub app: tide::Server<()>,
 }
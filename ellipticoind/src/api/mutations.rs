@@ -1,11 +1,11 @@
 use crate::{
     api::{
         graphql::{Context, Error},
-        helpers::validate_signature,
+        helpers::{validate_signature, validate_signature_with_signer, validate_transaction},
         types::{Bytes, Transaction},
     },
     constants::NEW_BLOCK_CHANNEL,
-    helpers::run_transaction,
+    helpers::run_signed_transaction,
     models,
     state::current_miner,
 };
@@ -20,16 +20,32 @@ impl Mutations {
         _context: &Context,
         transaction: Bytes,
     ) -> Result<Transaction, Error> {
-        let transaction_request = validate_signature(&transaction.0)?;
-        let transaction = run_transaction(transaction_request).await;
+        let transaction_request = validate_transaction(&transaction.0)?;
+        let transaction = run_signed_transaction(transaction_request, &transaction.0)
+            .await
+            .map_err(|error| Error(error.message))?;
         Ok(Transaction::from(transaction))
     }
 
     pub async fn post_block(_context: &Context, block: Bytes) -> Result<bool, Error> {
-        let block: (models::block::Block, Vec<models::transaction::Transaction>) =
-            validate_signature(&block.0)?;
-        let state = block.clone().0.apply(block.1).await;
-        println!("Applied block #{}", block.0.number);
+        let ((block, transactions), signer): (
+            (models::block::Block, Vec<models::transaction::Transaction>),
+            [u8; 32],
+        ) = validate_signature_with_signer(&block.0)?;
+        block
+            .is_valid(signer, &transactions)
+            .await
+            .map_err(|error| {
+                Error(format!(
+                    "Invalid block #{}: {}",
+                    block.number, error.message
+                ))
+            })?;
+        let state = block
+            .apply(transactions)
+            .await
+            .map_err(|error| Error(error.message))?;
+        println!("Applied block #{}", block.number);
         NEW_BLOCK_CHANNEL.0.send(state).await;
 
         Ok(true)
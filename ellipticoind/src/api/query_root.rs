@@ -5,17 +5,19 @@ use crate::{
     },
     config::get_pg_connection,
     diesel::{BelongingToDsl, RunQueryDsl},
+    mempool::{self, MEMPOOL},
     models,
-    models::transaction::next_nonce,
     schema::{blocks, blocks::columns::number, transactions},
-    state::IN_MEMORY_STATE,
+    state::STATE,
+    system_contracts,
     system_contracts::{
         api::InMemoryAPI, ellipticoin::get_issuance_rewards, exchange,
         exchange::constants::BASE_TOKEN, token, token::BASE_FACTOR,
     },
+    transaction::TransactionRequest,
 };
 use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
-use ellipticoin::Address;
+use ellipticoin::{helpers::db_key, Address};
 use std::convert::TryInto;
 
 pub struct QueryRoot;
@@ -27,14 +29,16 @@ impl QueryRoot {
         _context: &Context,
         token_ids: Vec<TokenId>,
         address: Bytes,
+        block_number: Option<U32>,
     ) -> Result<Vec<Token>, Error> {
         let address: Address = address
             .0
             .clone()
             .try_into()
             .map_err(|e: Box<wasm_rpc::error::Error>| Error(e.to_string()))?;
-        let mut state = IN_MEMORY_STATE.lock().await;
-        let mut api = InMemoryAPI::new(&mut state, None);
+        let block_number = validate_block_number(block_number)?;
+        let mut state = STATE.lock().await;
+        let mut api = InMemoryAPI::at_block(&mut state, block_number);
         Ok(token_ids
             .iter()
             .cloned()
@@ -47,6 +51,23 @@ impl QueryRoot {
                     },
                     address.clone(),
                 );
+                let locked_balance = token::get_locked_balance(
+                    &mut api,
+                    ellipticoin::Token {
+                        issuer: issuer.as_str().into(),
+                        id: id.0.clone().into(),
+                    },
+                    address.clone(),
+                );
+                let vested_balance = token::get_vested_balance(
+                    &mut api,
+                    ellipticoin::Token {
+                        issuer: issuer.as_str().into(),
+                        id: id.0.clone().into(),
+                    },
+                    address.clone(),
+                )
+                .unwrap_or(0);
                 let total_supply = token::get_total_supply(
                     &mut api,
                     ellipticoin::Token {
@@ -67,29 +88,85 @@ impl QueryRoot {
                     .unwrap_or(0)
                 };
 
+                let token = ellipticoin::Token {
+                    issuer: issuer.as_str().into(),
+                    id: id.0.clone().into(),
+                };
+                let paused = token::get_paused(&mut api, token.clone());
+                let frozen = token::get_frozen(&mut api, token.clone(), address.clone());
+                let metadata = token::get_metadata(&mut api, token.clone())
+                    .map(|metadata| TokenMetadata::from((token, metadata)));
+
                 Token {
                     issuer: issuer.as_str().into(),
                     id: id.clone().into(),
-                    balance: U64(balance),
-                    price: U64(price),
-                    total_supply: U64(total_supply),
+                    balance: U128(balance),
+                    locked_balance: U128(locked_balance),
+                    vested_balance: U128(vested_balance),
+                    price: U128(price),
+                    total_supply: U128(total_supply),
+                    paused,
+                    frozen,
+                    metadata,
                 }
             })
             .collect())
     }
 
+    async fn all_tokens(_context: &Context) -> Vec<TokenMetadata> {
+        let mut state = STATE.lock().await;
+        let mut api = InMemoryAPI::at_block(&mut state, None);
+        let registered_tokens = token::get_registered_tokens(&mut api);
+        let mut all_tokens = vec![];
+        for token in registered_tokens.iter() {
+            if let Some(metadata) = token::get_metadata(&mut api, token.clone()) {
+                all_tokens.push(TokenMetadata::from((token.clone(), metadata)));
+            }
+        }
+        for token in registered_tokens {
+            if let Some(metadata) = exchange::get_liquidity_token_metadata(&mut api, token.clone())
+            {
+                all_tokens.push(TokenMetadata::from((
+                    exchange::liquidity_token(token),
+                    metadata,
+                )));
+            }
+        }
+        all_tokens
+    }
+
+    async fn quote(
+        _context: &Context,
+        input_token: TokenId,
+        output_token: TokenId,
+        input_amount: U128,
+    ) -> Result<Quote, Error> {
+        let mut state = STATE.lock().await;
+        let mut api = InMemoryAPI::at_block(&mut state, None);
+        exchange::quote(
+            &mut api,
+            input_token.into(),
+            output_token.into(),
+            input_amount.0,
+        )
+        .map(Quote::from)
+        .map_err(|e| Error(e.message))
+    }
+
     async fn liquidity_tokens(
         _context: &Context,
         token_ids: Vec<TokenId>,
         address: Bytes,
+        block_number: Option<U32>,
     ) -> Result<Vec<LiquidityToken>, Error> {
         let address: Address = address
             .0
             .clone()
             .try_into()
             .map_err(|e: Box<wasm_rpc::error::Error>| Error(e.to_string()))?;
-        let mut state = IN_MEMORY_STATE.lock().await;
-        let mut api = InMemoryAPI::new(&mut state, None);
+        let block_number = validate_block_number(block_number)?;
+        let mut state = STATE.lock().await;
+        let mut api = InMemoryAPI::at_block(&mut state, block_number);
         Ok(token_ids
             .iter()
             .cloned()
@@ -110,11 +187,11 @@ impl QueryRoot {
                 LiquidityToken {
                     issuer,
                     id,
-                    balance: U64(balance),
-                    price: U64(price),
-                    total_supply: U64(total_supply),
-                    pool_supply_of_token: U64(pool_supply_of_token),
-                    pool_supply_of_base_token: U64(pool_supply_of_base_token),
+                    balance: U128(balance),
+                    price: U128(price),
+                    total_supply: U128(total_supply),
+                    pool_supply_of_token: U128(pool_supply_of_token),
+                    pool_supply_of_base_token: U128(pool_supply_of_base_token),
                 }
             })
             .collect())
@@ -142,12 +219,20 @@ impl QueryRoot {
         Some(block_number.into())
     }
 
-    async fn issuance_rewards(_context: &Context, address: Bytes) -> Option<U64> {
-        let mut state = IN_MEMORY_STATE.lock().await;
-        let mut api = InMemoryAPI::new(&mut state, None);
-        let issuance_rewards =
-            get_issuance_rewards(&mut api, <Vec<u8>>::from(address).try_into().ok()?);
-        Some(issuance_rewards.into())
+    async fn issuance_rewards(
+        _context: &Context,
+        address: Bytes,
+        block_number: Option<U32>,
+    ) -> Result<Option<U128>, Error> {
+        let address: Address = match <Vec<u8>>::from(address).try_into() {
+            Ok(address) => address,
+            Err(_) => return Ok(None),
+        };
+        let block_number = validate_block_number(block_number)?;
+        let mut state = STATE.lock().await;
+        let mut api = InMemoryAPI::at_block(&mut state, block_number);
+        let issuance_rewards = get_issuance_rewards(&mut api, address);
+        Ok(Some(issuance_rewards.into()))
     }
 
     async fn current_block(_context: &Context) -> Block {
@@ -177,6 +262,52 @@ impl QueryRoot {
             .map(Transaction::from)
     }
 
+    async fn transaction_by_hash(_context: &Context, hash: Bytes) -> Option<Transaction> {
+        let con = get_pg_connection();
+        transactions::dsl::transactions
+            .filter(transactions::dsl::hash.eq(hash.0))
+            .order(transactions::dsl::id.asc())
+            .first::<models::Transaction>(&con)
+            .optional()
+            .ok()
+            .flatten()
+            .map(Transaction::from)
+    }
+
+    async fn events(
+        _context: &Context,
+        contract: Option<String>,
+        name: Option<String>,
+        address: Option<Bytes>,
+        first: Option<U32>,
+        after: Option<U32>,
+    ) -> Vec<Event> {
+        models::Event::filter(
+            contract,
+            name,
+            address.map(|address| address.0),
+            first.map(|first| first.0),
+            after.map(|after| after.0),
+        )
+        .into_iter()
+        .map(Event::from)
+        .collect()
+    }
+
+    // Runs a CBOR encoded `TransactionRequest` without committing its changes
+    async fn simulate_transaction(
+        _context: &Context,
+        transaction: Bytes,
+    ) -> Result<SimulatedTransaction, Error> {
+        let transaction_request: TransactionRequest =
+            serde_cbor::from_slice(&transaction.0).map_err(|e| Error(e.to_string()))?;
+        let mut state = STATE.lock().await;
+        let mut api = InMemoryAPI::simulation(&mut state, transaction_request.clone());
+        let return_value = system_contracts::run(&mut api, transaction_request);
+        let state_changes = api.state_changes;
+        Ok(SimulatedTransaction::from((return_value, state_changes)))
+    }
+
     async fn transactions_by_contract_function(
         _context: &Context,
         sender_address: Bytes,
@@ -200,7 +331,129 @@ impl QueryRoot {
             .collect()
     }
 
-    async fn next_nonce(_context: &Context, address: Bytes) -> U32 {
-        U32(next_nonce(address.0))
+    async fn next_nonce(_context: &Context, address: Bytes) -> Result<U32, Error> {
+        let address: [u8; 32] = address.0[..]
+            .try_into()
+            .map_err(|_| Error("invalid address".to_string()))?;
+        Ok(U32(mempool::next_nonce(address).await))
+    }
+
+    async fn pending_transactions(
+        _context: &Context,
+        address: Bytes,
+    ) -> Result<Vec<PendingTransaction>, Error> {
+        let address: [u8; 32] = address.0[..]
+            .try_into()
+            .map_err(|_| Error("invalid address".to_string()))?;
+        Ok(MEMPOOL
+            .lock()
+            .await
+            .pending(&address)
+            .into_iter()
+            .map(PendingTransaction::from)
+            .collect())
+    }
+
+    async fn balance_proof(
+        _context: &Context,
+        token_id: TokenId,
+        address: Bytes,
+    ) -> Result<StateProof, Error> {
+        let address: Address = address
+            .0
+            .clone()
+            .try_into()
+            .map_err(|e: Box<wasm_rpc::error::Error>| Error(e.to_string()))?;
+        Ok(state_proof(db_key(
+            token::CONTRACT_NAME,
+            &[
+                [token::Namespace::Balance as u8].to_vec(),
+                ellipticoin::Token::from(token_id).into(),
+                address.into(),
+            ]
+            .concat(),
+        ))
+        .await)
+    }
+
+    async fn pool_supply_of_token_proof(_context: &Context, token_id: TokenId) -> StateProof {
+        state_proof(db_key(
+            exchange::CONTRACT_NAME,
+            &[
+                [exchange::Namespace::PoolSupplyOfToken as u8].to_vec(),
+                ellipticoin::Token::from(token_id).into(),
+            ]
+            .concat(),
+        ))
+        .await
     }
+
+    async fn pool_supply_of_base_token_proof(_context: &Context, token_id: TokenId) -> StateProof {
+        state_proof(db_key(
+            exchange::CONTRACT_NAME,
+            &[
+                [exchange::Namespace::PoolSupplyOfBaseToken as u8].to_vec(),
+                ellipticoin::Token::from(token_id).into(),
+            ]
+            .concat(),
+        ))
+        .await
+    }
+
+    async fn issuance_rewards_proof(
+        _context: &Context,
+        address: Bytes,
+    ) -> Result<StateProof, Error> {
+        let address: Address = address
+            .0
+            .clone()
+            .try_into()
+            .map_err(|e: Box<wasm_rpc::error::Error>| Error(e.to_string()))?;
+        Ok(state_proof(db_key(
+            system_contracts::ellipticoin::CONTRACT_NAME,
+            &[
+                [system_contracts::ellipticoin::Namespace::IssuanceRewards as u8].to_vec(),
+                address.into(),
+            ]
+            .concat(),
+        ))
+        .await)
+    }
+}
+
+fn validate_block_number(block_number: Option<U32>) -> Result<Option<u32>, Error> {
+    let block_number = match block_number {
+        Some(block_number) => block_number.0,
+        None => return Ok(None),
+    };
+    let current_block_number = models::Block::current_block_number();
+    if block_number > current_block_number {
+        return Err(Error(format!(
+            "Block #{} has not been sealed",
+            block_number
+        )));
+    }
+    // Nodes that were started from a state snapshot don't have versions from
+    // before the snapshot
+    if models::Block::first_block_number()
+        .is_none_or(|first_block_number| block_number < first_block_number)
+    {
+        return Err(Error(format!(
+            "State at block #{} is not available",
+            block_number
+        )));
+    }
+    if models::StateVersion::is_pruned(block_number, current_block_number) {
+        return Err(Error(format!(
+            "State at block #{} has been pruned",
+            block_number
+        )));
+    }
+    Ok(Some(block_number))
+}
+
+async fn state_proof(key: Vec<u8>) -> StateProof {
+    let state = STATE.lock().await;
+    let (value, proof) = state.prove(&key);
+    StateProof::from((key, value, state.committed_state_root(), proof))
 }
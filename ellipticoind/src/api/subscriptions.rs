@@ -0,0 +1,113 @@
+use crate::{
+    api::{
+        graphql::{Context, Error},
+        types::*,
+    },
+    config::get_pg_connection,
+    constants::BLOCK_BROADCASTER,
+    diesel::{ExpressionMethods, QueryDsl, RunQueryDsl},
+    models,
+    schema::transactions,
+    state::STATE,
+    system_contracts::{api::InMemoryAPI, token},
+};
+use diesel::OptionalExtension;
+use ellipticoin::Address;
+use futures::{
+    future,
+    stream::{self, BoxStream},
+};
+use std::convert::TryInto;
+
+pub struct Subscriptions;
+#[juniper::graphql_subscription(
+    Context = Context,
+)]
+impl Subscriptions {
+    async fn blocks(_context: &Context) -> BoxStream<'static, Block> {
+        BLOCK_BROADCASTER.subscribe().map(Block::from).boxed()
+    }
+
+    async fn transaction(_context: &Context, hash: Bytes) -> BoxStream<'static, Transaction> {
+        // Subscribe before looking the transaction up so it can't be
+        // confirmed in between
+        let new_blocks = BLOCK_BROADCASTER.subscribe();
+        let confirmed_transaction = transactions::dsl::transactions
+            .filter(transactions::dsl::hash.eq(hash.0.clone()))
+            .order(transactions::dsl::id.asc())
+            .first::<models::Transaction>(&get_pg_connection())
+            .optional()
+            .unwrap();
+        stream::iter(confirmed_transaction)
+            .chain(
+                new_blocks
+                    .flat_map(|(_block, transactions)| stream::iter(transactions))
+                    .filter(move |transaction| {
+                        future::ready(transaction.hash.as_ref() == Some(&hash.0))
+                    }),
+            )
+            .take(1)
+            .map(Transaction::from)
+            .boxed()
+    }
+
+    // Emits the current balances followed by the balances that changed in
+    // each new block.
+    async fn balances(
+        _context: &Context,
+        token_ids: Vec<TokenId>,
+        addresses: Vec<Bytes>,
+    ) -> Result<BoxStream<'static, Vec<Balance>>, Error> {
+        let addresses = addresses
+            .into_iter()
+            .map(|address| address.0.try_into())
+            .collect::<Result<Vec<Address>, Box<wasm_rpc::error::Error>>>()
+            .map_err(|e| Error(e.to_string()))?;
+        let new_blocks = BLOCK_BROADCASTER.subscribe();
+        let current_balances = balances(&token_ids, &addresses).await;
+        Ok(stream::once(future::ready(current_balances.clone()))
+            .chain(
+                new_blocks
+                    .then(move |_| {
+                        let token_ids = token_ids.clone();
+                        let addresses = addresses.clone();
+                        async move { balances(&token_ids, &addresses).await }
+                    })
+                    .scan(current_balances, |previous_balances, balances| {
+                        let changed_balances = balances
+                            .iter()
+                            .zip(previous_balances.iter())
+                            .filter(|(balance, previous_balance)| {
+                                balance.balance.0 != previous_balance.balance.0
+                            })
+                            .map(|(balance, _)| balance.clone())
+                            .collect::<Vec<Balance>>();
+                        *previous_balances = balances;
+                        future::ready(Some(changed_balances))
+                    })
+                    .filter(|changed_balances| future::ready(!changed_balances.is_empty())),
+            )
+            .boxed())
+    }
+}
+
+async fn balances(token_ids: &[TokenId], addresses: &[Address]) -> Vec<Balance> {
+    let mut state = STATE.lock().await;
+    let mut api = InMemoryAPI::at_block(&mut state, None);
+    let mut balances = vec![];
+    for address in addresses {
+        for token_id in token_ids {
+            balances.push(Balance {
+                address: Bytes(address.clone().to_vec()),
+                token_id: token_id.id.clone(),
+                issuer: token_id.issuer.clone(),
+                balance: U128(token::get_balance(
+                    &mut api,
+                    token_id.clone().into(),
+                    address.clone(),
+                )),
+            });
+        }
+    }
+    balances
+}
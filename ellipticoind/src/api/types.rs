@@ -1,13 +1,23 @@
-use crate::models;
+use crate::{
+    mempool, models,
+    system_contracts::{decode_key, exchange, token},
+};
+use ellipticoin::merkle::{Hash, Proof};
 use juniper::{ParseScalarResult, ParseScalarValue, Value};
+use serde::Deserialize;
 
 #[derive(Clone, Debug)]
 pub struct Token {
     pub id: Bytes,
     pub issuer: String,
-    pub price: U64,
-    pub balance: U64,
-    pub total_supply: U64,
+    pub price: U128,
+    pub balance: U128,
+    pub locked_balance: U128,
+    pub vested_balance: U128,
+    pub total_supply: U128,
+    pub paused: bool,
+    pub frozen: bool,
+    pub metadata: Option<TokenMetadata>,
 }
 
 #[juniper::graphql_object]
@@ -20,28 +30,131 @@ impl Token {
         self.issuer.clone()
     }
 
-    fn price(&self) -> U64 {
+    fn price(&self) -> U128 {
         self.price.clone()
     }
 
-    fn balance(&self) -> U64 {
+    fn balance(&self) -> U128 {
         self.balance.clone()
     }
 
-    fn total_supply(&self) -> U64 {
+    // Funds that haven't been released yet
+    fn locked_balance(&self) -> U128 {
+        self.locked_balance.clone()
+    }
+
+    // Locked funds that can be released now
+    fn vested_balance(&self) -> U128 {
+        self.vested_balance.clone()
+    }
+
+    fn total_supply(&self) -> U128 {
         self.total_supply.clone()
     }
+
+    fn paused(&self) -> bool {
+        self.paused
+    }
+
+    // Whether the queried address has been frozen
+    fn frozen(&self) -> bool {
+        self.frozen
+    }
+
+    fn metadata(&self) -> Option<TokenMetadata> {
+        self.metadata.clone()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TokenMetadata {
+    pub id: Bytes,
+    pub issuer: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: i32,
+    pub website: String,
+}
+
+#[juniper::graphql_object]
+impl TokenMetadata {
+    fn id(&self) -> Bytes {
+        self.id.clone()
+    }
+
+    fn issuer(&self) -> String {
+        self.issuer.clone()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn symbol(&self) -> String {
+        self.symbol.clone()
+    }
+
+    fn decimals(&self) -> i32 {
+        self.decimals
+    }
+
+    fn website(&self) -> String {
+        self.website.clone()
+    }
+}
+
+impl From<(ellipticoin::Token, token::Metadata)> for TokenMetadata {
+    fn from((token, metadata): (ellipticoin::Token, token::Metadata)) -> Self {
+        Self {
+            id: Bytes(token.id.into_vec()),
+            issuer: match token.issuer {
+                ellipticoin::Address::PublicKey(public_key) => base64::encode(public_key),
+                ellipticoin::Address::Contract(contract) => contract,
+            },
+            name: metadata.name,
+            symbol: metadata.symbol,
+            decimals: metadata.decimals as i32,
+            website: metadata.website,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Balance {
+    pub address: Bytes,
+    pub token_id: Bytes,
+    pub issuer: String,
+    pub balance: U128,
+}
+
+#[juniper::graphql_object]
+impl Balance {
+    fn address(&self) -> Bytes {
+        self.address.clone()
+    }
+
+    fn token_id(&self) -> Bytes {
+        self.token_id.clone()
+    }
+
+    fn issuer(&self) -> String {
+        self.issuer.clone()
+    }
+
+    fn balance(&self) -> U128 {
+        self.balance.clone()
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct LiquidityToken {
     pub id: Bytes,
     pub issuer: String,
-    pub balance: U64,
-    pub price: U64,
-    pub total_supply: U64,
-    pub pool_supply_of_token: U64,
-    pub pool_supply_of_base_token: U64,
+    pub balance: U128,
+    pub price: U128,
+    pub total_supply: U128,
+    pub pool_supply_of_token: U128,
+    pub pool_supply_of_base_token: U128,
 }
 
 #[juniper::graphql_object]
@@ -54,27 +167,83 @@ impl LiquidityToken {
         self.issuer.clone()
     }
 
-    fn balance(&self) -> U64 {
+    fn balance(&self) -> U128 {
         self.balance.clone()
     }
 
-    fn price(&self) -> U64 {
+    fn price(&self) -> U128 {
         self.price.clone()
     }
 
-    fn total_supply(&self) -> U64 {
+    fn total_supply(&self) -> U128 {
         self.total_supply.clone()
     }
 
-    fn pool_supply_of_token(&self) -> U64 {
+    fn pool_supply_of_token(&self) -> U128 {
         self.pool_supply_of_token.clone()
     }
 
-    fn pool_supply_of_base_token(&self) -> U64 {
+    fn pool_supply_of_base_token(&self) -> U128 {
         self.pool_supply_of_base_token.clone()
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct Quote {
+    pub input_amount: U128,
+    pub base_token_amount: U128,
+    pub output_amount: U128,
+    pub input_token_fee: U128,
+    pub base_token_fee: U128,
+    pub price_impact: U128,
+    pub spot_price: U128,
+}
+
+#[juniper::graphql_object]
+impl Quote {
+    fn input_amount(&self) -> U128 {
+        self.input_amount.clone()
+    }
+
+    fn base_token_amount(&self) -> U128 {
+        self.base_token_amount.clone()
+    }
+
+    fn output_amount(&self) -> U128 {
+        self.output_amount.clone()
+    }
+
+    fn input_token_fee(&self) -> U128 {
+        self.input_token_fee.clone()
+    }
+
+    fn base_token_fee(&self) -> U128 {
+        self.base_token_fee.clone()
+    }
+
+    fn price_impact(&self) -> U128 {
+        self.price_impact.clone()
+    }
+
+    fn spot_price(&self) -> U128 {
+        self.spot_price.clone()
+    }
+}
+
+impl From<exchange::Quote> for Quote {
+    fn from(quote: exchange::Quote) -> Self {
+        Self {
+            input_amount: U128(quote.input_amount),
+            base_token_amount: U128(quote.base_token_amount),
+            output_amount: U128(quote.output_amount),
+            input_token_fee: U128(quote.input_token_fee),
+            base_token_fee: U128(quote.base_token_fee),
+            price_impact: U128(quote.price_impact),
+            spot_price: U128(quote.spot_price),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Block {
     pub number: U32,
@@ -82,6 +251,7 @@ pub struct Block {
     pub sealed: bool,
     pub memory_changeset_hash: Bytes,
     pub storage_changeset_hash: Bytes,
+    pub state_root: Bytes,
 }
 
 #[juniper::graphql_object]
@@ -105,6 +275,47 @@ impl Block {
     fn storage_changeset_hash(&self) -> Bytes {
         self.storage_changeset_hash.clone()
     }
+
+    fn state_root(&self) -> Bytes {
+        self.state_root.clone()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StateProof {
+    pub key: Bytes,
+    pub value: Bytes,
+    pub state_root: Bytes,
+    pub leaf_key_hash: Option<Bytes>,
+    pub leaf_value_hash: Option<Bytes>,
+    pub siblings: Vec<Bytes>,
+}
+
+#[juniper::graphql_object]
+impl StateProof {
+    fn key(&self) -> Bytes {
+        self.key.clone()
+    }
+
+    fn value(&self) -> Bytes {
+        self.value.clone()
+    }
+
+    fn state_root(&self) -> Bytes {
+        self.state_root.clone()
+    }
+
+    fn leaf_key_hash(&self) -> Option<Bytes> {
+        self.leaf_key_hash.clone()
+    }
+
+    fn leaf_value_hash(&self) -> Option<Bytes> {
+        self.leaf_value_hash.clone()
+    }
+
+    fn siblings(&self) -> Vec<Bytes> {
+        self.siblings.clone()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -120,6 +331,7 @@ pub struct Transaction {
     pub arguments: Bytes,
     pub return_value: Bytes,
     pub raw: Bytes,
+    pub hash: Option<Bytes>,
 }
 
 #[juniper::graphql_object]
@@ -167,6 +379,196 @@ impl Transaction {
     fn raw(&self) -> Bytes {
         self.raw.clone()
     }
+
+    fn hash(&self) -> Option<Bytes> {
+        self.hash.clone()
+    }
+
+    fn state_changes(&self) -> Vec<StateChange> {
+        models::StateChange::for_transaction(self.id.0 as i32)
+            .into_iter()
+            .map(StateChange::from)
+            .collect()
+    }
+
+    fn events(&self) -> Vec<Event> {
+        models::Event::for_transaction(self.id.0 as i32)
+            .into_iter()
+            .map(Event::from)
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub id: U32,
+    pub transaction_id: U32,
+    pub contract: String,
+    pub name: String,
+    pub addresses: Vec<Bytes>,
+    pub data: Bytes,
+}
+
+#[juniper::graphql_object]
+impl Event {
+    fn id(&self) -> U32 {
+        self.id.clone()
+    }
+
+    fn transaction_id(&self) -> U32 {
+        self.transaction_id.clone()
+    }
+
+    fn contract(&self) -> String {
+        self.contract.clone()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn addresses(&self) -> Vec<Bytes> {
+        self.addresses.clone()
+    }
+
+    fn data(&self) -> Bytes {
+        self.data.clone()
+    }
+}
+
+impl From<models::Event> for Event {
+    fn from(event: models::Event) -> Self {
+        Self {
+            id: U32(event.id as u32),
+            transaction_id: U32(event.transaction_id as u32),
+            contract: event.contract,
+            name: event.name,
+            addresses: event.addresses.into_iter().map(Bytes).collect(),
+            data: Bytes(event.data),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StateChange {
+    pub key: Bytes,
+    pub contract: Option<String>,
+    pub namespace: Option<String>,
+    pub subkey: Option<Bytes>,
+    pub old_value: Bytes,
+    pub new_value: Bytes,
+}
+
+#[juniper::graphql_object]
+impl StateChange {
+    fn key(&self) -> Bytes {
+        self.key.clone()
+    }
+
+    fn contract(&self) -> Option<String> {
+        self.contract.clone()
+    }
+
+    fn namespace(&self) -> Option<String> {
+        self.namespace.clone()
+    }
+
+    fn subkey(&self) -> Option<Bytes> {
+        self.subkey.clone()
+    }
+
+    fn old_value(&self) -> Bytes {
+        self.old_value.clone()
+    }
+
+    fn new_value(&self) -> Bytes {
+        self.new_value.clone()
+    }
+}
+
+impl From<models::StateChange> for StateChange {
+    fn from(state_change: models::StateChange) -> Self {
+        Self::from((
+            state_change.key,
+            state_change.old_value,
+            state_change.new_value,
+        ))
+    }
+}
+
+impl From<(Vec<u8>, Vec<u8>, Vec<u8>)> for StateChange {
+    fn from((key, old_value, new_value): (Vec<u8>, Vec<u8>, Vec<u8>)) -> Self {
+        let decoded_key = decode_key(&key);
+        Self {
+            contract: decoded_key
+                .clone()
+                .map(|(contract, _, _)| contract.to_string()),
+            namespace: decoded_key
+                .clone()
+                .map(|(_, namespace, _)| namespace.to_string()),
+            subkey: decoded_key.map(|(_, _, subkey)| Bytes(subkey)),
+            key: Bytes(key),
+            old_value: Bytes(old_value),
+            new_value: Bytes(new_value),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimulatedTransaction {
+    pub return_value: Bytes,
+    pub error: Option<TransactionError>,
+    pub state_changes: Vec<StateChange>,
+}
+
+#[juniper::graphql_object]
+impl SimulatedTransaction {
+    fn return_value(&self) -> Bytes {
+        self.return_value.clone()
+    }
+
+    fn error(&self) -> Option<TransactionError> {
+        self.error.clone()
+    }
+
+    fn state_changes(&self) -> Vec<StateChange> {
+        self.state_changes.clone()
+    }
+}
+
+// Node errors such as "Contract Not Found" don't have a code
+#[derive(Clone, Debug, Deserialize)]
+pub struct TransactionError {
+    #[serde(default)]
+    pub code: Option<u32>,
+    pub message: String,
+}
+
+#[juniper::graphql_object]
+impl TransactionError {
+    fn code(&self) -> Option<U32> {
+        self.code.map(U32)
+    }
+
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+impl From<(serde_cbor::Value, Vec<(Vec<u8>, Vec<u8>, Vec<u8>)>)> for SimulatedTransaction {
+    fn from(
+        (return_value, state_changes): (serde_cbor::Value, Vec<(Vec<u8>, Vec<u8>, Vec<u8>)>),
+    ) -> Self {
+        Self {
+            error: serde_cbor::value::from_value::<Result<serde_cbor::Value, TransactionError>>(
+                return_value.clone(),
+            )
+            .ok()
+            .and_then(Result::err),
+            return_value: Bytes(serde_cbor::to_vec(&return_value).unwrap()),
+            state_changes: state_changes.into_iter().map(StateChange::from).collect(),
+        }
+    }
 }
 
 impl From<models::Transaction> for Transaction {
@@ -183,9 +585,96 @@ impl From<models::Transaction> for Transaction {
             arguments: transaction.arguments.into(),
             return_value: Bytes(transaction.return_value),
             raw: Bytes(transaction.raw),
+            hash: transaction.hash.map(Bytes),
         }
     }
 }
+#[derive(Clone, Debug)]
+pub struct PendingTransaction {
+    pub hash: Bytes,
+    pub network_id: U64,
+    pub contract: String,
+    pub sender: Bytes,
+    pub nonce: U32,
+    pub function: String,
+    pub arguments: Bytes,
+    pub fee: U128,
+    pub raw: Bytes,
+}
+
+#[juniper::graphql_object]
+impl PendingTransaction {
+    fn hash(&self) -> Bytes {
+        self.hash.clone()
+    }
+
+    fn network_id(&self) -> U64 {
+        self.network_id.clone()
+    }
+
+    fn contract(&self) -> String {
+        self.contract.clone()
+    }
+
+    fn sender(&self) -> Bytes {
+        self.sender.clone()
+    }
+
+    fn nonce(&self) -> U32 {
+        self.nonce.clone()
+    }
+
+    fn function(&self) -> String {
+        self.function.clone()
+    }
+
+    fn arguments(&self) -> Bytes {
+        self.arguments.clone()
+    }
+
+    fn fee(&self) -> U128 {
+        self.fee.clone()
+    }
+
+    fn raw(&self) -> Bytes {
+        self.raw.clone()
+    }
+}
+
+impl From<&mempool::PendingTransaction> for PendingTransaction {
+    fn from(pending_transaction: &mempool::PendingTransaction) -> Self {
+        let transaction_request = &pending_transaction.transaction_request;
+        Self {
+            hash: Bytes(pending_transaction.hash.to_vec()),
+            network_id: U64(transaction_request.network_id as u64),
+            contract: transaction_request.contract.clone(),
+            sender: Bytes(transaction_request.sender.to_vec()),
+            nonce: U32(transaction_request.nonce),
+            function: transaction_request.function.clone(),
+            arguments: Bytes(serde_cbor::to_vec(&transaction_request.arguments).unwrap()),
+            fee: U128(pending_transaction.fee),
+            raw: Bytes(pending_transaction.raw.clone()),
+        }
+    }
+}
+
+impl From<(Vec<u8>, Vec<u8>, Hash, Proof)> for StateProof {
+    fn from((key, value, state_root, proof): (Vec<u8>, Vec<u8>, Hash, Proof)) -> Self {
+        Self {
+            key: Bytes(key),
+            value: Bytes(value),
+            state_root: Bytes(state_root.to_vec()),
+            leaf_key_hash: proof.leaf.map(|(key_hash, _)| Bytes(key_hash.to_vec())),
+            leaf_value_hash: proof.leaf.map(|(_, value_hash)| Bytes(value_hash.to_vec())),
+            siblings: proof
+                .siblings
+                .iter()
+                .map(|sibling| Bytes(sibling.to_vec()))
+                .collect(),
+        }
+    }
+}
+
 impl From<(models::Block, Vec<models::Transaction>)> for Block {
     fn from(block: (models::Block, Vec<models::Transaction>)) -> Block {
         Self {
@@ -193,6 +682,7 @@ impl From<(models::Block, Vec<models::Transaction>)> for Block {
             sealed: block.0.sealed,
             memory_changeset_hash: Bytes(block.0.memory_changeset_hash),
             storage_changeset_hash: Bytes(block.0.storage_changeset_hash),
+            state_root: Bytes(block.0.state_root),
             transactions: block
                 .1
                 .into_iter()
@@ -217,6 +707,21 @@ impl From<u64> for U64 {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct U128(pub u128);
+
+impl From<U128> for String {
+    fn from(n: U128) -> Self {
+        n.0.to_string()
+    }
+}
+
+impl From<u128> for U128 {
+    fn from(n: u128) -> Self {
+        U128(n)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct U32(pub u32);
 impl From<U32> for String {
@@ -304,6 +809,28 @@ where
     }
 }
 
+#[juniper::graphql_scalar(description = "U128")]
+impl<S> GraphQLScalar for U128
+where
+    S: ScalarValue,
+{
+    fn resolve(&self) -> Value {
+        Value::scalar(self.0.to_string())
+    }
+
+    fn from_input_value(v: &InputValue) -> Option<U128> {
+        v.as_scalar_value()
+            .and_then(|v| v.as_str())
+            .map(|v| v.parse())
+            .and_then(Result::ok)
+            .map(U128)
+    }
+
+    fn from_str<'a>(value: ScalarToken<'a>) -> ParseScalarResult<'a, S> {
+        <String as ParseScalarValue<S>>::from_str(value)
+    }
+}
+
 #[juniper::graphql_scalar(description = "U32")]
 impl<S> GraphQLScalar for U32
 where
@@ -8,6 +8,8 @@ pub struct Block {
     pub memory_changeset_hash: Vec<u8>,
     #[serde(with = "serde_bytes")]
     pub storage_changeset_hash: Vec<u8>,
+    #[serde(default, with = "serde_bytes")]
+    pub state_root: Vec<u8>,
     pub transactions: Vec<Transaction>,
     pub sealed: bool,
 }
@@ -25,6 +27,10 @@ pub struct Transaction {
     return_value: serde_cbor::Value,
     #[serde(with = "serde_bytes")]
     pub sender: Vec<u8>,
+    #[serde(default, with = "serde_bytes")]
+    pub raw: Vec<u8>,
+    #[serde(default, with = "serde_bytes")]
+    pub hash: Option<Vec<u8>>,
 }
 
 impl From<(models::Block, Vec<models::Transaction>)> for Block {
@@ -33,6 +39,7 @@ impl From<(models::Block, Vec<models::Transaction>)> for Block {
             number: block.0.number as u32,
             memory_changeset_hash: block.0.memory_changeset_hash.clone(),
             storage_changeset_hash: block.0.storage_changeset_hash.clone(),
+            state_root: block.0.state_root.clone(),
             sealed: block.0.sealed,
             transactions: block
                 .1
@@ -56,6 +63,8 @@ impl From<models::Transaction> for Transaction {
             function: transaction.function.clone(),
             arguments: serde_cbor::from_slice(&transaction.arguments).unwrap(),
             return_value: serde_cbor::from_slice(&transaction.return_value).unwrap(),
+            raw: transaction.raw.clone(),
+            hash: transaction.hash.clone(),
         }
     }
 }
@@ -68,6 +77,7 @@ impl From<Block> for (models::Block, Vec<models::Transaction>) {
                 memory_changeset_hash: block.memory_changeset_hash.clone(),
                 storage_changeset_hash: block.storage_changeset_hash.clone(),
                 sealed: block.sealed,
+                state_root: block.state_root.clone(),
             },
             block
                 .transactions
@@ -91,7 +101,37 @@ impl From<Transaction> for models::Transaction {
             function: transaction.function.clone(),
             arguments: serde_cbor::to_vec(&transaction.arguments).unwrap(),
             return_value: serde_cbor::to_vec(&transaction.return_value).unwrap(),
-            raw: vec![],
+            raw: transaction.raw.clone(),
+            hash: transaction.hash.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_cbor::Value;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_deserialize_block_without_state_root() {
+        let mut block = BTreeMap::new();
+        block.insert(Value::Text("number".to_string()), Value::Integer(1));
+        block.insert(
+            Value::Text("memory_changeset_hash".to_string()),
+            Value::Bytes(vec![]),
+        );
+        block.insert(
+            Value::Text("storage_changeset_hash".to_string()),
+            Value::Bytes(vec![]),
+        );
+        block.insert(
+            Value::Text("transactions".to_string()),
+            Value::Array(vec![]),
+        );
+        block.insert(Value::Text("sealed".to_string()), Value::Bool(true));
+        let block: Block =
+            serde_cbor::from_slice(&serde_cbor::to_vec(&Value::Map(block)).unwrap()).unwrap();
+        assert!(block.state_root.is_empty());
+    }
+}
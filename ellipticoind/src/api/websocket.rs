@@ -0,0 +1,77 @@
+use crate::{
+    api::{
+        graphql::{Context, Schema},
+        mutations::Mutations,
+        query_root::QueryRoot,
+        subscriptions::Subscriptions,
+    },
+    config::websocket_socket,
+};
+use async_std::{
+    net::{TcpListener, TcpStream},
+    task::spawn,
+};
+use async_tungstenite::{
+    accept_hdr_async,
+    tungstenite::{
+        handshake::server::{ErrorResponse, Request, Response},
+        http::HeaderValue,
+        Message,
+    },
+};
+use futures::{future, StreamExt};
+use juniper::DefaultScalarValue;
+use juniper_graphql_ws::{ArcSchema, ClientMessage, Connection, ConnectionConfig};
+use std::sync::Arc;
+
+// Serves GraphQL subscriptions using the `graphql-ws` protocol
+pub async fn listen() {
+    let listener = TcpListener::bind(websocket_socket()).await.unwrap();
+    let schema = Arc::new(Schema::new(QueryRoot, Mutations, Subscriptions));
+    let mut incoming = listener.incoming();
+    while let Some(stream) = incoming.next().await {
+        if let Ok(stream) = stream {
+            spawn(handle_connection(stream, schema.clone()));
+        }
+    }
+}
+
+async fn handle_connection(stream: TcpStream, schema: Arc<Schema>) {
+    let websocket = match accept_hdr_async(stream, accept_graphql_ws).await {
+        Ok(websocket) => websocket,
+        Err(_) => return,
+    };
+    let (websocket_sink, websocket_stream) = websocket.split();
+    let (connection_sink, connection_stream) =
+        Connection::new(ArcSchema(schema), ConnectionConfig::new(Context {})).split();
+    let client_messages = websocket_stream
+        .take_while(|message| future::ready(matches!(message, Ok(message) if !message.is_close())))
+        .filter_map(|message| {
+            future::ready(message.ok().and_then(|message| {
+                serde_json::from_str::<ClientMessage<DefaultScalarValue>>(message.to_text().ok()?)
+                    .ok()
+            }))
+        })
+        .map(Ok);
+    let server_messages = connection_stream
+        .map(|message| Ok(Message::text(serde_json::to_string(&message).unwrap())));
+    let _ = future::join(
+        client_messages.forward(connection_sink),
+        server_messages.forward(websocket_sink),
+    )
+    .await;
+}
+
+// The signature is set by tungstenite's `Callback` trait so the error can't be
+// boxed
+#[allow(clippy::result_large_err)]
+fn accept_graphql_ws(
+    _request: &Request,
+    mut response: Response,
+) -> Result<Response, ErrorResponse> {
+    response.headers_mut().insert(
+        "Sec-WebSocket-Protocol",
+        HeaderValue::from_static("graphql-ws"),
+    );
+    Ok(response)
+}
@@ -0,0 +1,58 @@
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use std::sync::Mutex;
+
+// Sends every message to each current subscriber. Unlike `BroadcastChannel`
+// it doesn't hold a receiver of its own so messages aren't queued when
+// nobody is subscribed.
+pub struct Broadcaster<T> {
+    subscribers: Mutex<Vec<UnboundedSender<T>>>,
+}
+
+impl<T: Clone> Broadcaster<T> {
+    pub fn new() -> Self {
+        Self {
+            subscribers: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn subscribe(&self) -> UnboundedReceiver<T> {
+        let (sender, receiver) = unbounded();
+        self.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    // Subscribers whose receivers were dropped are removed.
+    pub fn broadcast(&self, message: T) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| subscriber.unbounded_send(message.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_broadcast() {
+        let broadcaster = Broadcaster::new();
+        broadcaster.broadcast(1);
+        let mut receiver1 = broadcaster.subscribe();
+        let mut receiver2 = broadcaster.subscribe();
+        broadcaster.broadcast(2);
+        assert_eq!(receiver1.try_recv().unwrap(), 2);
+        assert_eq!(receiver2.try_recv().unwrap(), 2);
+        assert!(receiver1.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_dropped_subscribers_are_removed() {
+        let broadcaster = Broadcaster::new();
+        let receiver = broadcaster.subscribe();
+        broadcaster.broadcast(1);
+        drop(receiver);
+        broadcaster.broadcast(2);
+        assert!(broadcaster.subscribers.lock().unwrap().is_empty());
+    }
+}
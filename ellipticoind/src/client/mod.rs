@@ -55,8 +55,12 @@ struct PostTransaction;
 
 pub async fn post_transaction(host: &str, transaction_request: TransactionRequest) -> Transaction {
     let signed_transaction = sign(transaction_request).await;
+    post_signed_transaction(host, &serde_cbor::to_vec(&signed_transaction).unwrap()).await
+}
+
+pub async fn post_signed_transaction(host: &str, signed_transaction: &[u8]) -> Transaction {
     let request_body = PostTransaction::build_query(post_transaction::Variables {
-        transaction: base64_encode(signed_transaction),
+        transaction: base64::encode(signed_transaction),
     });
 
     let mut res = surf::post(host_uri(&host))
@@ -81,6 +85,7 @@ impl From<post_transaction::PostTransactionPostTransaction> for Transaction {
             arguments: base64::decode(transaction.arguments).unwrap_or(vec![]),
             return_value: base64::decode(transaction.return_value).unwrap_or(vec![]),
             raw: base64::decode(transaction.raw).unwrap_or(vec![]),
+            hash: transaction.hash.and_then(|hash| base64::decode(hash).ok()),
         }
     }
 }
@@ -122,7 +127,9 @@ impl From<block::BlockBlock> for (models::block::Block, Vec<models::transaction:
             models::block::Block {
                 number: block.number.parse().unwrap(),
                 sealed: block.sealed,
-                ..Default::default()
+                memory_changeset_hash: base64::decode(&block.memory_changeset_hash).unwrap(),
+                storage_changeset_hash: base64::decode(&block.storage_changeset_hash).unwrap(),
+                state_root: base64::decode(&block.state_root).unwrap(),
             },
             block
                 .transactions
@@ -146,6 +153,10 @@ impl From<&block::BlockBlockTransactions> for models::transaction::Transaction {
             arguments: base64::decode(&transaction.arguments).unwrap(),
             return_value: base64::decode(&transaction.return_value).unwrap(),
             raw: base64::decode(&transaction.raw).unwrap(),
+            hash: transaction
+                .hash
+                .as_ref()
+                .map(|hash| base64::decode(hash).unwrap()),
         }
     }
 }
@@ -28,6 +28,10 @@ pub struct Opts {
     pub port: u16,
     #[clap(long = "rocksdb-path", default_value = "./ellipticoind/db")]
     pub rocksdb_path: String,
+    #[clap(long = "in-memory-state")]
+    pub in_memory_state: bool,
+    #[clap(long = "state-retention")]
+    pub state_retention: Option<u32>,
     #[clap(
         long = "genesis-path",
         default_value = "./ellipticoind/dist/genesis.cbor"
@@ -38,6 +42,10 @@ pub struct Opts {
         default_value = "./ellipticoind/static/genesis-blocks.cbor"
     )]
     pub genesis_blocks_path: String,
+    #[clap(long = "state-snapshot-path")]
+    pub state_snapshot_path: Option<String>,
+    #[clap(long = "mempool-fee-priority")]
+    pub mempool_fee_priority: bool,
     #[clap(long = "save-state")]
     pub save_state: bool,
     #[clap(subcommand)]
@@ -63,6 +71,15 @@ pub enum SubCommand {
         #[clap(long = "file", default_value = "genesis-blocks.cbor")]
         file: String,
     },
+    #[clap(name = "verify-state")]
+    VerifyState,
+    #[clap(name = "dump-state")]
+    DumpState {
+        #[clap(long = "at-block")]
+        at_block: Option<u32>,
+        #[clap(long = "file", default_value = "state.cbor")]
+        file: String,
+    },
 }
 
 lazy_static! {
@@ -125,6 +142,14 @@ pub fn socket() -> SocketAddr {
     (OPTS.bind_address.parse::<IpAddr>().unwrap(), OPTS.port).into()
 }
 
+pub fn websocket_socket() -> SocketAddr {
+    (
+        OPTS.bind_address.parse::<IpAddr>().unwrap(),
+        OPTS.websocket_port,
+    )
+        .into()
+}
+
 pub fn signing_key() -> SigningKey {
     SigningKey::try_from(
         <[u8; 32]>::try_from(
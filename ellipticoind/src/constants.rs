@@ -1,34 +1,27 @@
 use crate::{
-    models::Transaction, system_contracts::ellipticoin::State, transaction::TransactionRequest,
+    broadcast::Broadcaster,
+    models::{Block, Transaction},
+    system_contracts::ellipticoin::State,
 };
 use async_std::sync::{channel, Mutex, Receiver, Sender};
 use broadcaster::BroadcastChannel;
-use futures::channel::oneshot;
 use std::{sync::Arc, time::Duration};
 
 lazy_static! {
+    // Blocks below this height were sealed before the current validation
+    // rules were enforced so fields they're missing are accepted.
+    pub static ref ACTIVATION_HEIGHT: u32 = 2_500_000;
     pub static ref BLOCK_TIME: Duration = Duration::from_secs(3);
     pub static ref TOKEN_CONTRACT: String = "Ellipticoin".to_string();
-    pub static ref TRANSACTION_QUEUE_SIZE: usize = 1000;
-    pub static ref TRANSACTION_QUEUE: (
-        Sender<(TransactionRequest, oneshot::Sender<Transaction>)>,
-        Receiver<(TransactionRequest, oneshot::Sender<Transaction>)>
-    ) = channel(*TRANSACTION_QUEUE_SIZE);
+    pub static ref MEMPOOL_SIZE: usize = 1000;
+    pub static ref MAX_PENDING_TRANSACTIONS_PER_SENDER: usize = 64;
+    pub static ref TRANSACTION_TIMEOUT: Duration = Duration::from_secs(30);
+    pub static ref MAX_EVENTS_PAGE_SIZE: u32 = 100;
     pub static ref NEW_BLOCK_CHANNEL: (Sender<State>, Receiver<State>) = channel(1);
     pub static ref SYNCING: Arc<Mutex<bool>> = Arc::new(Mutex::new(false));
     pub static ref WEB_SOCKET_BROADCASTER: BroadcastChannel<(u32, String)> =
         BroadcastChannel::new();
-}
-
-impl TRANSACTION_QUEUE {
-    pub async fn push(
-        &self,
-        transaction_request: TransactionRequest,
-    ) -> oneshot::Receiver<Transaction> {
-        let (sender, receiver) = oneshot::channel();
-        self.0.send((transaction_request, sender)).await;
-        receiver
-    }
+    pub static ref BLOCK_BROADCASTER: Broadcaster<(Block, Vec<Transaction>)> = Broadcaster::new();
 }
 
 impl WEB_SOCKET_BROADCASTER {
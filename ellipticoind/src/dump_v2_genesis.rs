@@ -8,7 +8,7 @@ use crate::{
     schema::blocks::dsl as blocks_dsl,
     schema::transactions::dsl as transactions_dsl,
     start_up,
-    state::IN_MEMORY_STATE,
+    state::STATE,
 };
 use ellipticoin::Address;
 use ellipticoin::Address::PublicKey;
@@ -95,7 +95,7 @@ pub async fn dump_v2_genesis() {
         .unwrap();
     start_up::load_genesis_state().await;
     run_transactions_in_db().await;
-    let state = IN_MEMORY_STATE.lock().await;
+    let state: HashMap<Vec<u8>, Vec<u8>> = STATE.lock().await.committed_iter().collect();
     let mut v2_genesis_state = HashMap::new();
     state
         .iter()
@@ -423,7 +423,7 @@ pub async fn run_transactions_in_db() {
         ))
         .load::<Transaction>(&pg_db)
         .unwrap();
-    let mut state = IN_MEMORY_STATE.lock().await;
+    let mut state = STATE.lock().await;
     for mut transaction in transactions {
         if transaction.id >= 3104941 {
             break;
@@ -6,9 +6,51 @@ lazy_static! {
     pub static ref CONTRACT_NOT_FOUND: Error = Error {
         message: "Contract Not Found".to_string(),
     };
+    pub static ref INVALID_CHANGESET_HASH: Error = Error {
+        message: "Block changeset hash does not match the applied transactions".to_string(),
+    };
+    pub static ref INVALID_BLOCK_SIGNER: Error = Error {
+        message: "Block was not signed by the current miner".to_string(),
+    };
+    pub static ref INVALID_BLOCK_NUMBER: Error = Error {
+        message: "Block number is not the current block height plus one".to_string(),
+    };
+    pub static ref INVALID_SEAL_TRANSACTION: Error = Error {
+        message: "Block does not end with a valid seal transaction".to_string(),
+    };
+    pub static ref INVALID_NETWORK_ID: Error = Error {
+        message: "Transaction network id does not match this network".to_string(),
+    };
+    pub static ref SENDER_IS_NOT_SIGNER: Error = Error {
+        message: "Transaction sender does not match its signer".to_string(),
+    };
+    pub static ref TRANSACTION_ALREADY_PENDING: Error = Error {
+        message: "Transaction is already pending".to_string(),
+    };
+    pub static ref NONCE_ALREADY_PENDING: Error = Error {
+        message: "A transaction with this nonce is already pending".to_string(),
+    };
+    pub static ref NONCE_ALREADY_USED: Error = Error {
+        message: "Nonce has already been used".to_string(),
+    };
+    pub static ref TOO_MANY_PENDING_TRANSACTIONS: Error = Error {
+        message: "Sender has too many pending transactions".to_string(),
+    };
+    pub static ref MEMPOOL_FULL: Error = Error {
+        message: "Mempool is full".to_string(),
+    };
+    pub static ref TRANSACTION_DROPPED: Error = Error {
+        message: "Transaction was dropped from the mempool".to_string(),
+    };
+    pub static ref TRANSACTION_TIMED_OUT: Error = Error {
+        message: "Transaction is still pending, look it up by its hash".to_string(),
+    };
+    pub static ref INVALID_STATE_ROOT: Error = Error {
+        message: "Block state root does not match the applied transactions".to_string(),
+    };
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, Debug)]
 pub struct Error {
     pub message: String,
 }
@@ -1,8 +1,18 @@
 use crate::{
-    client::post_transaction, config::verification_key, constants::TRANSACTION_QUEUE,
-    models::transaction::Transaction, state::current_miner, transaction::TransactionRequest,
+    client::post_signed_transaction,
+    config::verification_key,
+    constants::TRANSACTION_TIMEOUT,
+    error::{Error, TRANSACTION_DROPPED, TRANSACTION_TIMED_OUT},
+    mempool,
+    models::transaction::Transaction,
+    state::current_miner,
+    transaction::TransactionRequest,
+};
+use async_std::{
+    future::{timeout, Future, TimeoutError},
+    prelude::FutureExt as asyncStdFutureExt,
+    task::sleep,
 };
-use async_std::{future::Future, prelude::FutureExt as asyncStdFutureExt, task::sleep};
 use futures::future::FutureExt;
 use serde_cbor::Value;
 use sha2::{Digest, Sha256};
@@ -25,12 +35,27 @@ pub fn bytes_to_value(bytes: Vec<u8>) -> Value {
         .into()
 }
 
-pub async fn run_transaction(transaction_request: TransactionRequest) -> Transaction {
+pub async fn run_transaction(
+    transaction_request: TransactionRequest,
+) -> Result<Transaction, Error> {
+    let signed_transaction = transaction_request.sign();
+    run_signed_transaction(transaction_request, &signed_transaction).await
+}
+
+// Transactions posted by users are forwarded to the current miner with the
+// user's signature rather than being re-signed by this node.
+pub async fn run_signed_transaction(
+    transaction_request: TransactionRequest,
+    signed_transaction: &[u8],
+) -> Result<Transaction, Error> {
     if current_miner().await.address == verification_key() {
-        let receiver = TRANSACTION_QUEUE.push(transaction_request).await;
-        receiver.await.unwrap()
+        let receiver = mempool::push(transaction_request, signed_transaction.to_vec()).await?;
+        match timeout(*TRANSACTION_TIMEOUT, receiver).await {
+            Ok(transaction) => transaction.map_err(|_| TRANSACTION_DROPPED.clone()),
+            Err(TimeoutError { .. }) => Err(TRANSACTION_TIMED_OUT.clone()),
+        }
     } else {
-        post_transaction(&current_miner().await.host, transaction_request).await
+        Ok(post_signed_transaction(&current_miner().await.host, signed_transaction).await)
     }
 }
 
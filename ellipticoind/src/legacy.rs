@@ -23,7 +23,11 @@ pub async fn run<API: ellipticoin::API>(api: &mut API, transaction: &mut Transac
     {
         run_remove_liquidity(api, transaction);
     } else {
-        system_contracts::run(api, TransactionRequest::from(transaction.clone()));
+        system_contracts::run_in_block(
+            api,
+            TransactionRequest::from(transaction.clone()),
+            transaction.block_number as u32,
+        );
     }
 }
 
@@ -51,7 +55,7 @@ pub fn run_remove_liquidity<API: ellipticoin::API>(api: &mut API, transaction: &
 pub fn remove_liquidity<API: ellipticoin::API>(
     api: &mut API,
     token: Token,
-    amount: u64,
+    amount: u128,
 ) -> Result<(), Box<Error>> {
     let pool_supply_of_token = get_pool_supply_of_token(api, token.clone());
     let pool_supply_of_base_token = get_pool_supply_of_base_token(api, token.clone());
@@ -60,8 +64,7 @@ pub fn remove_liquidity<API: ellipticoin::API>(
     burn_liquidity(
         api,
         token.clone(),
-        (total_suply_of_liquidity_token as u128 * amount as u128 / pool_supply_of_token as u128)
-            as u64,
+        total_suply_of_liquidity_token * amount / pool_supply_of_token,
     )?;
     debit_pool_supply_of_base_token(
         api,
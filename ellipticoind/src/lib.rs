@@ -17,12 +17,14 @@ extern crate maplit;
 mod api;
 pub mod dump_v2_genesis;
 mod block_broadcaster;
+mod broadcast;
 pub mod client;
 pub mod config;
 mod constants;
 mod error;
 mod helpers;
 mod legacy;
+pub mod mempool;
 mod miner;
 pub mod models;
 mod pg;
@@ -30,6 +32,8 @@ mod schema;
 mod start_up;
 mod state;
 mod static_files;
+mod storage;
 pub mod sub_commands;
 pub mod system_contracts;
 pub mod transaction;
+pub mod verify_state;
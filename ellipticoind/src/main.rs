@@ -1,7 +1,8 @@
 use ellipticoind::{
     config::{SubCommand, OPTS},
     dump_v2_genesis,
-    sub_commands::{self, dump_blocks, generate_keypair},
+    sub_commands::{self, dump_blocks, dump_state, generate_keypair},
+    verify_state,
 };
 
 #[async_std::main]
@@ -12,6 +13,8 @@ async fn main() {
         Some(SubCommand::DumpBlocks { block_number, file }) => {
             dump_blocks(*block_number, &file).await
         }
+        Some(SubCommand::VerifyState) => verify_state::verify_state().await,
+        Some(SubCommand::DumpState { at_block, file }) => dump_state(*at_block, file).await,
         None => sub_commands::main().await,
     }
 }
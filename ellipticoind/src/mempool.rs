@@ -0,0 +1,586 @@
+use crate::{
+    client::post_signed_transaction,
+    config::OPTS,
+    constants::{MAX_PENDING_TRANSACTIONS_PER_SENDER, MEMPOOL_SIZE},
+    error::{
+        Error, MEMPOOL_FULL, NONCE_ALREADY_PENDING, NONCE_ALREADY_USED,
+        TOO_MANY_PENDING_TRANSACTIONS, TRANSACTION_ALREADY_PENDING,
+    },
+    models::Transaction,
+    state::STATE,
+    system_contracts::{
+        api::InMemoryAPI,
+        ellipticoin,
+        token::{self, constants::ELC},
+    },
+    transaction::TransactionRequest,
+};
+use ::ellipticoin::Address;
+use async_std::{
+    sync::{channel, Mutex, Receiver, Sender},
+    task,
+};
+use futures::channel::oneshot;
+use serde_cbor::value::from_value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+lazy_static! {
+    pub static ref MEMPOOL: Mutex<Mempool> = Mutex::new(Mempool::new(
+        *MEMPOOL_SIZE,
+        *MAX_PENDING_TRANSACTIONS_PER_SENDER,
+        OPTS.mempool_fee_priority,
+    ));
+    static ref NEW_TRANSACTION_CHANNEL: (Sender<()>, Receiver<()>) = channel(1);
+}
+
+pub struct PendingTransaction {
+    pub hash: [u8; 32],
+    pub transaction_request: TransactionRequest,
+    pub raw: Vec<u8>,
+    pub fee: u128,
+    pub result_sender: Option<oneshot::Sender<Transaction>>,
+    sequence: u64,
+}
+
+impl PendingTransaction {
+    pub fn new(transaction_request: TransactionRequest, raw: Vec<u8>) -> Self {
+        Self {
+            hash: transaction_request.hash(),
+            fee: fee(&transaction_request),
+            transaction_request,
+            raw,
+            result_sender: None,
+            sequence: 0,
+        }
+    }
+}
+
+pub struct Mempool {
+    senders: HashMap<[u8; 32], BTreeMap<u32, PendingTransaction>>,
+    hashes: HashSet<[u8; 32]>,
+    max_size: usize,
+    max_pending_transactions_per_sender: usize,
+    fee_priority: bool,
+    sequence: u64,
+}
+
+impl Mempool {
+    pub fn new(
+        max_size: usize,
+        max_pending_transactions_per_sender: usize,
+        fee_priority: bool,
+    ) -> Self {
+        Self {
+            senders: HashMap::new(),
+            hashes: HashSet::new(),
+            max_size,
+            max_pending_transactions_per_sender,
+            fee_priority,
+            sequence: 0,
+        }
+    }
+
+    pub fn pending(&self, sender: &[u8; 32]) -> Vec<&PendingTransaction> {
+        self.senders
+            .get(sender)
+            .map(|transactions| transactions.values().collect())
+            .unwrap_or(vec![])
+    }
+
+    pub fn insert(
+        &mut self,
+        mut pending_transaction: PendingTransaction,
+        expected_nonce: u32,
+    ) -> Result<(), Error> {
+        let sender = pending_transaction.transaction_request.sender;
+        let nonce = pending_transaction.transaction_request.nonce;
+        if self.hashes.contains(&pending_transaction.hash) {
+            return Err(TRANSACTION_ALREADY_PENDING.clone());
+        }
+        if nonce < expected_nonce {
+            return Err(Error {
+                message: format!(
+                    "{}: expected {}",
+                    NONCE_ALREADY_USED.message, expected_nonce
+                ),
+            });
+        }
+        let priority = self.priority(&pending_transaction);
+        let pending_transactions = self.senders.get(&sender);
+        if let Some(existing) =
+            pending_transactions.and_then(|transactions| transactions.get(&nonce))
+        {
+            if priority <= self.priority(existing) {
+                return Err(NONCE_ALREADY_PENDING.clone());
+            }
+            self.remove(&sender, nonce);
+        } else if pending_transactions
+            .map(|transactions| transactions.len() >= self.max_pending_transactions_per_sender)
+            .unwrap_or(false)
+        {
+            return Err(TOO_MANY_PENDING_TRANSACTIONS.clone());
+        } else if self.hashes.len() >= self.max_size {
+            match self.eviction_candidate() {
+                Some((evicted_sender, evicted_nonce, evicted_priority))
+                    if evicted_priority < priority =>
+                {
+                    self.remove(&evicted_sender, evicted_nonce);
+                }
+                _ => return Err(MEMPOOL_FULL.clone()),
+            }
+        }
+        self.sequence += 1;
+        pending_transaction.sequence = self.sequence;
+        self.hashes.insert(pending_transaction.hash);
+        self.senders
+            .entry(sender)
+            .or_default()
+            .insert(nonce, pending_transaction);
+        Ok(())
+    }
+
+    // Returns the next transaction that can be mined. A sender's transactions
+    // are only ready once every lower nonce has been used.
+    pub fn pop<F: FnMut(&[u8; 32]) -> u32>(
+        &mut self,
+        mut expected_nonce: F,
+    ) -> Option<PendingTransaction> {
+        let mut next: Option<([u8; 32], u32, u128, u64)> = None;
+        for (sender, transactions) in self.senders.iter_mut() {
+            let nonce = expected_nonce(sender);
+            let ready = transactions.split_off(&nonce);
+            for stale_transaction in std::mem::replace(transactions, ready).values() {
+                self.hashes.remove(&stale_transaction.hash);
+            }
+            if let Some(transaction) = transactions.get(&nonce) {
+                let priority = if self.fee_priority {
+                    transaction.fee
+                } else {
+                    0
+                };
+                if next
+                    .map(|(_, _, next_priority, next_sequence)| {
+                        (priority, std::cmp::Reverse(transaction.sequence))
+                            > (next_priority, std::cmp::Reverse(next_sequence))
+                    })
+                    .unwrap_or(true)
+                {
+                    next = Some((*sender, nonce, priority, transaction.sequence));
+                }
+            }
+        }
+        self.senders
+            .retain(|_sender, transactions| !transactions.is_empty());
+        next.and_then(|(sender, nonce, _, _)| self.remove(&sender, nonce))
+    }
+
+    // A tip only buys priority if the sender can pay it along with the tips of
+    // their other pending transactions.
+    pub fn funded_fee(&self, pending_transaction: &PendingTransaction, balance: u128) -> u128 {
+        let transaction_request = &pending_transaction.transaction_request;
+        let is_funded = self
+            .pending(&transaction_request.sender)
+            .iter()
+            .filter(|pending| pending.transaction_request.nonce != transaction_request.nonce)
+            .try_fold(pending_transaction.fee, |total, pending| {
+                total.checked_add(pending.fee)
+            })
+            .map(|total| total <= balance)
+            .unwrap_or(false);
+        if is_funded {
+            pending_transaction.fee
+        } else {
+            0
+        }
+    }
+
+    // Returns the sender's next nonce after their contiguous pending
+    // transactions.
+    pub fn next_nonce(&self, sender: &[u8; 32], expected_nonce: u32) -> u32 {
+        let mut nonce = expected_nonce;
+        if let Some(transactions) = self.senders.get(sender) {
+            while transactions.contains_key(&nonce) {
+                nonce += 1;
+            }
+        }
+        nonce
+    }
+
+    // Removes every pending transaction. Each sender's transactions are
+    // returned in nonce order.
+    pub fn drain(&mut self) -> Vec<PendingTransaction> {
+        self.hashes.clear();
+        self.senders
+            .drain()
+            .flat_map(|(_sender, transactions)| transactions.into_values())
+            .collect()
+    }
+
+    fn remove(&mut self, sender: &[u8; 32], nonce: u32) -> Option<PendingTransaction> {
+        let transactions = self.senders.get_mut(sender)?;
+        let pending_transaction = transactions.remove(&nonce)?;
+        if transactions.is_empty() {
+            self.senders.remove(sender);
+        }
+        self.hashes.remove(&pending_transaction.hash);
+        Some(pending_transaction)
+    }
+
+    // Only a sender's highest nonce can be evicted so no gaps are left behind.
+    // The lowest priority, most recently received transaction goes first.
+    fn eviction_candidate(&self) -> Option<([u8; 32], u32, u128)> {
+        self.senders
+            .iter()
+            .filter_map(|(sender, transactions)| {
+                transactions
+                    .iter()
+                    .next_back()
+                    .map(|(nonce, transaction)| (*sender, *nonce, transaction))
+            })
+            .min_by_key(|(_sender, _nonce, transaction)| {
+                (
+                    self.priority(transaction),
+                    std::cmp::Reverse(transaction.sequence),
+                )
+            })
+            .map(|(sender, nonce, transaction)| (sender, nonce, self.priority(transaction)))
+    }
+
+    fn priority(&self, pending_transaction: &PendingTransaction) -> u128 {
+        if self.fee_priority {
+            pending_transaction.fee
+        } else {
+            0
+        }
+    }
+}
+
+// Transactions that tip the current miner are prioritized by the size of the tip.
+fn fee(transaction_request: &TransactionRequest) -> u128 {
+    if transaction_request.contract == ellipticoin::CONTRACT_NAME
+        && transaction_request.function == "transfer_to_current_miner"
+    {
+        transaction_request
+            .arguments
+            .first()
+            .and_then(|amount| from_value(amount.clone()).ok())
+            .unwrap_or(0)
+    } else {
+        0
+    }
+}
+
+pub async fn push(
+    transaction_request: TransactionRequest,
+    raw: Vec<u8>,
+) -> Result<oneshot::Receiver<Transaction>, Error> {
+    let (result_sender, receiver) = oneshot::channel();
+    let mut pending_transaction = PendingTransaction::new(transaction_request, raw);
+    pending_transaction.result_sender = Some(result_sender);
+    let mut state = STATE.lock().await;
+    let mut api = InMemoryAPI::new(&mut state, None);
+    let sender = pending_transaction.transaction_request.sender;
+    let expected_nonce = expected_nonce(&mut api, &sender);
+    let balance = token::get_balance(&mut api, ELC.clone(), Address::PublicKey(sender));
+    let mut mempool = MEMPOOL.lock().await;
+    pending_transaction.fee = mempool.funded_fee(&pending_transaction, balance);
+    mempool.insert(pending_transaction, expected_nonce)?;
+    let _ = NEW_TRANSACTION_CHANNEL.0.try_send(());
+    Ok(receiver)
+}
+
+pub async fn next() -> PendingTransaction {
+    loop {
+        {
+            let mut state = STATE.lock().await;
+            let mut api = InMemoryAPI::new(&mut state, None);
+            if let Some(pending_transaction) = MEMPOOL
+                .lock()
+                .await
+                .pop(|sender| expected_nonce(&mut api, sender))
+            {
+                return pending_transaction;
+            }
+        }
+        NEW_TRANSACTION_CHANNEL.1.recv().await.unwrap();
+    }
+}
+
+// Transactions still pending when another node becomes the miner are
+// forwarded to it so the callers waiting on them get a result.
+pub async fn forward(host: String) {
+    for pending_transaction in MEMPOOL.lock().await.drain() {
+        let host = host.clone();
+        task::spawn(async move {
+            let transaction = post_signed_transaction(&host, &pending_transaction.raw).await;
+            if let Some(result_sender) = pending_transaction.result_sender {
+                let _ = result_sender.send(transaction);
+            }
+        });
+    }
+}
+
+pub async fn next_nonce(sender: [u8; 32]) -> u32 {
+    let mut state = STATE.lock().await;
+    let mut api = InMemoryAPI::new(&mut state, None);
+    let expected_nonce = expected_nonce(&mut api, &sender);
+    MEMPOOL.lock().await.next_nonce(&sender, expected_nonce)
+}
+
+fn expected_nonce<API: ::ellipticoin::API>(api: &mut API, sender: &[u8; 32]) -> u32 {
+    ellipticoin::get_nonce(api, Address::PublicKey(*sender)) + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ellipticoin_test_framework::constants::actors::{ALICE, BOB};
+
+    fn pending_transaction(sender: [u8; 32], nonce: u32, fee: u128) -> PendingTransaction {
+        PendingTransaction::new(
+            TransactionRequest {
+                network_id: 0,
+                contract: ellipticoin::CONTRACT_NAME.to_string(),
+                sender,
+                nonce,
+                function: "transfer_to_current_miner".to_string(),
+                arguments: vec![serde_cbor::Value::Integer(fee as i128)],
+            },
+            vec![],
+        )
+    }
+
+    fn pop(mempool: &mut Mempool, nonces: &mut HashMap<[u8; 32], u32>) -> Option<(u32, u128)> {
+        let pending_transaction =
+            mempool.pop(|sender| nonces.get(sender).cloned().unwrap_or(0) + 1)?;
+        let sender = pending_transaction.transaction_request.sender;
+        nonces.insert(sender, pending_transaction.transaction_request.nonce);
+        Some((
+            pending_transaction.transaction_request.nonce,
+            pending_transaction.fee,
+        ))
+    }
+
+    #[test]
+    fn test_duplicate_transaction() {
+        let mut mempool = Mempool::new(10, 10, false);
+        mempool
+            .insert(pending_transaction(*ALICE, 1, 0), 1)
+            .unwrap();
+        assert_eq!(
+            mempool
+                .insert(pending_transaction(*ALICE, 1, 0), 1)
+                .unwrap_err()
+                .message,
+            TRANSACTION_ALREADY_PENDING.message
+        );
+        assert_eq!(
+            mempool
+                .insert(pending_transaction(*ALICE, 1, 1), 1)
+                .unwrap_err()
+                .message,
+            NONCE_ALREADY_PENDING.message
+        );
+        assert_eq!(mempool.hashes.len(), 1);
+    }
+
+    #[test]
+    fn test_used_nonce() {
+        let mut mempool = Mempool::new(10, 10, false);
+        assert_eq!(
+            mempool
+                .insert(pending_transaction(*ALICE, 1, 0), 2)
+                .unwrap_err()
+                .message,
+            format!("{}: expected 2", NONCE_ALREADY_USED.message)
+        );
+    }
+
+    #[test]
+    fn test_nonce_gap() {
+        let mut mempool = Mempool::new(10, 10, false);
+        let mut nonces = HashMap::new();
+        mempool
+            .insert(pending_transaction(*ALICE, 2, 0), 1)
+            .unwrap();
+        assert_eq!(pop(&mut mempool, &mut nonces), None);
+        mempool
+            .insert(pending_transaction(*ALICE, 1, 0), 1)
+            .unwrap();
+        assert_eq!(pop(&mut mempool, &mut nonces), Some((1, 0)));
+        assert_eq!(pop(&mut mempool, &mut nonces), Some((2, 0)));
+        assert_eq!(pop(&mut mempool, &mut nonces), None);
+        assert_eq!(mempool.hashes.len(), 0);
+    }
+
+    #[test]
+    fn test_stale_transactions_are_dropped() {
+        let mut mempool = Mempool::new(10, 10, false);
+        let mut nonces = HashMap::new();
+        mempool
+            .insert(pending_transaction(*ALICE, 1, 0), 1)
+            .unwrap();
+        mempool
+            .insert(pending_transaction(*ALICE, 2, 0), 1)
+            .unwrap();
+        nonces.insert(*ALICE, 1);
+        assert_eq!(pop(&mut mempool, &mut nonces), Some((2, 0)));
+        assert_eq!(mempool.hashes.len(), 0);
+    }
+
+    #[test]
+    fn test_sender_limit() {
+        let mut mempool = Mempool::new(10, 2, false);
+        mempool
+            .insert(pending_transaction(*ALICE, 1, 0), 1)
+            .unwrap();
+        mempool
+            .insert(pending_transaction(*ALICE, 2, 0), 1)
+            .unwrap();
+        assert_eq!(
+            mempool
+                .insert(pending_transaction(*ALICE, 3, 0), 1)
+                .unwrap_err()
+                .message,
+            TOO_MANY_PENDING_TRANSACTIONS.message
+        );
+        mempool.insert(pending_transaction(*BOB, 1, 0), 1).unwrap();
+    }
+
+    #[test]
+    fn test_full_mempool() {
+        let mut mempool = Mempool::new(2, 10, false);
+        mempool
+            .insert(pending_transaction(*ALICE, 1, 0), 1)
+            .unwrap();
+        mempool
+            .insert(pending_transaction(*ALICE, 2, 0), 1)
+            .unwrap();
+        assert_eq!(
+            mempool
+                .insert(pending_transaction(*BOB, 1, 1), 1)
+                .unwrap_err()
+                .message,
+            MEMPOOL_FULL.message
+        );
+    }
+
+    #[test]
+    fn test_eviction() {
+        let mut mempool = Mempool::new(2, 10, true);
+        let mut nonces = HashMap::new();
+        mempool
+            .insert(pending_transaction(*ALICE, 1, 0), 1)
+            .unwrap();
+        mempool
+            .insert(pending_transaction(*ALICE, 2, 0), 1)
+            .unwrap();
+        mempool.insert(pending_transaction(*BOB, 1, 1), 1).unwrap();
+        assert_eq!(mempool.hashes.len(), 2);
+        assert_eq!(pop(&mut mempool, &mut nonces), Some((1, 1)));
+        assert_eq!(pop(&mut mempool, &mut nonces), Some((1, 0)));
+        assert_eq!(pop(&mut mempool, &mut nonces), None);
+    }
+
+    #[test]
+    fn test_fee_priority() {
+        let mut mempool = Mempool::new(10, 10, true);
+        let mut nonces = HashMap::new();
+        mempool
+            .insert(pending_transaction(*ALICE, 1, 1), 1)
+            .unwrap();
+        mempool.insert(pending_transaction(*BOB, 1, 2), 1).unwrap();
+        mempool
+            .insert(pending_transaction(*ALICE, 2, 3), 1)
+            .unwrap();
+        assert_eq!(pop(&mut mempool, &mut nonces), Some((1, 2)));
+        assert_eq!(pop(&mut mempool, &mut nonces), Some((1, 1)));
+        assert_eq!(pop(&mut mempool, &mut nonces), Some((2, 3)));
+    }
+
+    #[test]
+    fn test_first_in_first_out() {
+        let mut mempool = Mempool::new(10, 10, false);
+        let mut nonces = HashMap::new();
+        mempool
+            .insert(pending_transaction(*ALICE, 1, 1), 1)
+            .unwrap();
+        mempool.insert(pending_transaction(*BOB, 1, 2), 1).unwrap();
+        assert_eq!(pop(&mut mempool, &mut nonces), Some((1, 1)));
+        assert_eq!(pop(&mut mempool, &mut nonces), Some((1, 2)));
+    }
+
+    #[test]
+    fn test_fee() {
+        let fee = u128::from(u64::MAX);
+        let transaction_request = TransactionRequest {
+            network_id: 0,
+            contract: ellipticoin::CONTRACT_NAME.to_string(),
+            sender: *ALICE,
+            nonce: 1,
+            function: "transfer_to_current_miner".to_string(),
+            arguments: vec![serde_cbor::Value::Integer(fee as i128)],
+        };
+        assert_eq!(super::fee(&transaction_request), fee);
+    }
+
+    #[test]
+    fn test_unfunded_fee() {
+        let mut mempool = Mempool::new(10, 10, true);
+        assert_eq!(mempool.funded_fee(&pending_transaction(*ALICE, 1, 2), 1), 0);
+        assert_eq!(mempool.funded_fee(&pending_transaction(*ALICE, 1, 2), 2), 2);
+        mempool
+            .insert(pending_transaction(*ALICE, 1, 2), 1)
+            .unwrap();
+        assert_eq!(mempool.funded_fee(&pending_transaction(*ALICE, 2, 2), 3), 0);
+        assert_eq!(mempool.funded_fee(&pending_transaction(*ALICE, 2, 2), 4), 2);
+        assert_eq!(mempool.funded_fee(&pending_transaction(*ALICE, 1, 3), 3), 3);
+        assert_eq!(mempool.funded_fee(&pending_transaction(*BOB, 1, 2), 2), 2);
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut mempool = Mempool::new(10, 10, false);
+        mempool
+            .insert(pending_transaction(*ALICE, 2, 0), 1)
+            .unwrap();
+        mempool
+            .insert(pending_transaction(*ALICE, 1, 0), 1)
+            .unwrap();
+        mempool.insert(pending_transaction(*BOB, 1, 0), 1).unwrap();
+        let mut drained = mempool
+            .drain()
+            .into_iter()
+            .map(|pending_transaction| {
+                (
+                    pending_transaction.transaction_request.sender,
+                    pending_transaction.transaction_request.nonce,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(drained.len(), 3);
+        drained.retain(|(sender, _nonce)| *sender == *ALICE);
+        assert_eq!(drained, vec![(*ALICE, 1), (*ALICE, 2)]);
+        assert_eq!(mempool.hashes.len(), 0);
+        assert!(mempool.pending(&ALICE).is_empty());
+    }
+
+    #[test]
+    fn test_next_nonce() {
+        let mut mempool = Mempool::new(10, 10, false);
+        assert_eq!(mempool.next_nonce(&ALICE, 1), 1);
+        mempool
+            .insert(pending_transaction(*ALICE, 1, 0), 1)
+            .unwrap();
+        mempool
+            .insert(pending_transaction(*ALICE, 2, 0), 1)
+            .unwrap();
+        mempool
+            .insert(pending_transaction(*ALICE, 4, 0), 1)
+            .unwrap();
+        assert_eq!(mempool.next_nonce(&ALICE, 1), 3);
+        assert_eq!(mempool.next_nonce(&ALICE, 2), 3);
+        assert_eq!(mempool.next_nonce(&ALICE, 5), 5);
+        assert_eq!(mempool.next_nonce(&BOB, 1), 1);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
     config::verification_key,
-    constants::{BLOCK_TIME, NEW_BLOCK_CHANNEL, TRANSACTION_QUEUE},
+    constants::{BLOCK_TIME, NEW_BLOCK_CHANNEL},
     helpers::run_for,
+    mempool,
     models::{Block, Transaction},
     state::current_miner,
     system_contracts::ellipticoin::State,
@@ -47,6 +48,9 @@ async fn mine_if_winner(state: State) {
     {
         mine_block(state.block_number).await
     } else {
+        if let Some(miner) = state.miners.first() {
+            mempool::forward(miner.host.clone()).await;
+        }
         sleep(*BLOCK_TIME).await;
     }
 }
@@ -56,13 +60,23 @@ async fn mine_block(block_number: u32) {
     let mut transaction_position = 0;
     run_for(*BLOCK_TIME, async {
         loop {
-            let (transaction_request, sender) = TRANSACTION_QUEUE.1.recv().await.unwrap();
-            let transaction =
-                Transaction::run(&block, transaction_request, transaction_position as i32).await;
+            let pending_transaction = mempool::next().await;
+            let transaction = Transaction::run(
+                &block,
+                pending_transaction.transaction_request,
+                pending_transaction.raw,
+                transaction_position as i32,
+            )
+            .await;
             transaction_position += 1;
-            sender.send(transaction).unwrap();
+            if let Some(result_sender) = pending_transaction.result_sender {
+                let _ = result_sender.send(transaction);
+            }
         }
     })
     .await;
-    block.seal(transaction_position).await;
+    let block_number = block.number;
+    if let Err(error) = block.seal(transaction_position).await {
+        println!("Failed to seal block #{}: {}", block_number, error.message);
+    }
 }
@@ -1,18 +1,31 @@
 use crate::{
     block_broadcaster::broadcast_block,
-    constants::{NEW_BLOCK_CHANNEL, WEB_SOCKET_BROADCASTER},
+    config::network_id,
+    constants::{ACTIVATION_HEIGHT, BLOCK_BROADCASTER, NEW_BLOCK_CHANNEL, WEB_SOCKET_BROADCASTER},
+    error::{
+        Error, INVALID_BLOCK_NUMBER, INVALID_BLOCK_SIGNER, INVALID_CHANGESET_HASH,
+        INVALID_NETWORK_ID, INVALID_SEAL_TRANSACTION, INVALID_STATE_ROOT, SENDER_IS_NOT_SIGNER,
+    },
+    helpers::sha256,
+    state::{current_miner, STATE},
+    storage::Storage,
 };
 pub use crate::{
     config::{get_pg_connection, verification_key},
     constants::TOKEN_CONTRACT,
     diesel::{BelongingToDsl, ExpressionMethods, QueryDsl, RunQueryDsl},
     helpers::bytes_to_value,
-    models::{self, HashOnion, Transaction},
+    models::{self, HashOnion, StateVersion, Transaction},
     schema::{blocks, blocks::dsl, transactions},
     system_contracts::ellipticoin::{self, Miner},
     transaction,
 };
-use diesel::dsl::{insert_into, max};
+use ::ellipticoin::StateAPI;
+use diesel::{
+    dsl::insert_into,
+    expression::dsl::{max, min},
+    Connection, OptionalExtension,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Queryable, Identifiable, Insertable, Clone, Debug, Serialize, Deserialize)]
@@ -22,6 +35,7 @@ pub struct Block {
     pub memory_changeset_hash: Vec<u8>,
     pub storage_changeset_hash: Vec<u8>,
     pub sealed: bool,
+    pub state_root: Vec<u8>,
 }
 
 #[derive(Insertable, Clone, Default, Debug, Serialize, Deserialize)]
@@ -39,6 +53,7 @@ impl Default for Block {
             memory_changeset_hash: vec![],
             storage_changeset_hash: vec![],
             sealed: false,
+            state_root: vec![],
         }
     }
 }
@@ -50,13 +65,21 @@ impl Block {
             memory_changeset_hash: vec![],
             storage_changeset_hash: vec![],
             sealed: false,
+            state_root: vec![],
         };
         block
     }
 
-    pub async fn apply(&self, transactions: Vec<models::Transaction>) -> ellipticoin::State {
+    pub async fn apply(
+        &self,
+        transactions: Vec<models::Transaction>,
+    ) -> Result<ellipticoin::State, Error> {
+        // The block is marked as sealed once its state is committed
         insert_into(dsl::blocks)
-            .values(self)
+            .values(&Block {
+                sealed: false,
+                ..self.clone()
+            })
             .execute(&get_pg_connection())
             .unwrap();
         let mut completed_transactions: Vec<Transaction> = vec![];
@@ -65,24 +88,101 @@ impl Block {
                 Transaction::run(
                     &self,
                     transaction::TransactionRequest::from(transaction.clone()),
+                    transaction.raw.clone(),
                     transaction.position,
                 )
                 .await,
             );
         }
+        let mut state = STATE.lock().await;
+        let (memory_changeset_hash, storage_changeset_hash) = self.changeset_hashes(&state);
+        if self.must_verify(&self.memory_changeset_hash)
+            && (self.memory_changeset_hash != memory_changeset_hash
+                || self.storage_changeset_hash != storage_changeset_hash)
+        {
+            state.revert();
+            self.discard();
+            return Err(INVALID_CHANGESET_HASH.clone());
+        }
+        if self.must_verify(&self.state_root) && self.state_root != state.state_root().to_vec() {
+            state.revert();
+            self.discard();
+            return Err(INVALID_STATE_ROOT.clone());
+        }
+        self.commit_state(&mut state);
+        drop(state);
         let state: ellipticoin::State =
             serde_cbor::from_slice::<Result<_, wasm_rpc::error::Error>>(
                 &completed_transactions.last().unwrap().return_value,
             )
             .unwrap()
             .unwrap();
+        BLOCK_BROADCASTER.broadcast((self.clone(), completed_transactions));
         WEB_SOCKET_BROADCASTER
             .broadcast(
                 state.block_number as u32,
                 state.miners.first().unwrap().host.clone(),
             )
             .await;
-        state
+        Ok(state)
+    }
+
+    // Blocks sealed before ACTIVATION_HEIGHT may have empty hashes. They're
+    // applied without checking them.
+    pub fn must_verify(&self, hash: &[u8]) -> bool {
+        !hash.is_empty() || self.number as u32 >= *ACTIVATION_HEIGHT
+    }
+
+    fn changeset_hashes(&self, state: &Storage) -> (Vec<u8>, Vec<u8>) {
+        let memory_changeset_hash = state.changeset_hash();
+        let previous_storage_changeset_hash = dsl::blocks
+            .select(dsl::storage_changeset_hash)
+            .filter(dsl::number.eq(self.number - 1))
+            .first::<Vec<u8>>(&get_pg_connection())
+            .optional()
+            .unwrap()
+            .unwrap_or(vec![]);
+        let storage_changeset_hash = sha256(
+            [
+                previous_storage_changeset_hash,
+                memory_changeset_hash.clone(),
+            ]
+            .concat(),
+        )
+        .to_vec();
+        (memory_changeset_hash, storage_changeset_hash)
+    }
+
+    // The block is sealed in the same Postgres transaction that records its
+    // state versions and before RocksDB is committed. If the node stops in
+    // between, the persisted state is behind the last sealed block and it's
+    // rebuilt on start up.
+    fn commit_state(&self, storage: &mut Storage) {
+        let pg_db = get_pg_connection();
+        pg_db
+            .transaction::<_, diesel::result::Error, _>(|| {
+                StateVersion::insert(&pg_db, self.number, storage);
+                diesel::update(dsl::blocks.filter(dsl::number.eq(self.number)))
+                    .set((
+                        dsl::sealed.eq(true),
+                        dsl::memory_changeset_hash.eq(&self.memory_changeset_hash),
+                        dsl::storage_changeset_hash.eq(&self.storage_changeset_hash),
+                        dsl::state_root.eq(&self.state_root),
+                    ))
+                    .execute(&pg_db)?;
+                Ok(())
+            })
+            .unwrap();
+        storage.commit();
+        StateVersion::prune(self.number);
+    }
+
+    fn discard(&self) {
+        let pg_db = get_pg_connection();
+        diesel::delete(Transaction::belonging_to(self))
+            .execute(&pg_db)
+            .unwrap();
+        diesel::delete(self).execute(&pg_db).unwrap();
     }
 
     pub fn insert(block_number: u32) -> Block {
@@ -97,46 +197,111 @@ impl Block {
         block
     }
 
-    pub async fn is_valid(&self) -> bool {
-        true
+    pub async fn is_valid(
+        &self,
+        signer: [u8; 32],
+        transactions: &[Transaction],
+    ) -> Result<(), Error> {
+        let current_miner = current_miner().await;
+        let next_block_number = Self::last_sealed_block_number().map_or(0, |number| number + 1);
+        self.validate(signer, transactions, &current_miner, next_block_number)
+    }
+
+    fn validate(
+        &self,
+        signer: [u8; 32],
+        transactions: &[Transaction],
+        current_miner: &Miner,
+        next_block_number: u32,
+    ) -> Result<(), Error> {
+        if signer != current_miner.address {
+            return Err(INVALID_BLOCK_SIGNER.clone());
+        }
+        if self.number as u32 != next_block_number {
+            return Err(INVALID_BLOCK_NUMBER.clone());
+        }
+        match transactions.last() {
+            Some(seal_transaction) if is_valid_seal(seal_transaction, current_miner) => (),
+            _ => return Err(INVALID_SEAL_TRANSACTION.clone()),
+        }
+        for transaction in transactions {
+            if transaction.network_id as u32 != network_id() {
+                return Err(INVALID_NETWORK_ID.clone());
+            }
+            if !transaction.is_signed_by_sender() {
+                return Err(SENDER_IS_NOT_SIGNER.clone());
+            }
+        }
+        Ok(())
     }
 
-    pub async fn seal(mut self, transaction_position: i64) {
+    pub async fn seal(mut self, transaction_position: i64) -> Result<(), Error> {
         let pg_db = get_pg_connection();
         let skin = HashOnion::peel(&pg_db);
         let seal_transaction_request = transaction::TransactionRequest::new(
             TOKEN_CONTRACT.clone(),
             "seal",
             vec![bytes_to_value(skin.clone())],
-        );
-        let seal_transaction =
-            Transaction::run(&self, seal_transaction_request, transaction_position as i32).await;
-        let state: ellipticoin::State =
-            serde_cbor::from_slice::<Result<_, wasm_rpc::error::Error>>(
-                &seal_transaction.return_value,
-            )
-            .unwrap()
-            .unwrap();
-        NEW_BLOCK_CHANNEL.0.send(state.clone()).await;
+        )
+        .await;
+        let seal_transaction = Transaction::run(
+            &self,
+            seal_transaction_request.clone(),
+            seal_transaction_request.sign(),
+            transaction_position as i32,
+        )
+        .await;
+        let state: ellipticoin::State = match serde_cbor::from_slice::<
+            Result<_, wasm_rpc::error::Error>,
+        >(&seal_transaction.return_value)
+        .unwrap()
+        {
+            Ok(state) => state,
+            Err(error) => {
+                STATE.lock().await.revert();
+                self.discard();
+                return Err(Error {
+                    message: error.message,
+                });
+            }
+        };
+        let mut storage = STATE.lock().await;
+        let (memory_changeset_hash, storage_changeset_hash) = self.changeset_hashes(&storage);
         self.sealed = true;
-        diesel::update(dsl::blocks.filter(dsl::number.eq(self.number.clone())))
-            .set(dsl::sealed.eq(true))
-            .execute(&pg_db)
-            .unwrap();
+        self.memory_changeset_hash = memory_changeset_hash;
+        self.storage_changeset_hash = storage_changeset_hash;
+        self.state_root = storage.state_root().to_vec();
+        self.commit_state(&mut storage);
+        drop(storage);
+        NEW_BLOCK_CHANNEL.0.send(state.clone()).await;
         let transactions = Transaction::belonging_to(&self)
             .order(transactions::dsl::position.asc())
             .load::<Transaction>(&pg_db)
             .unwrap();
-        broadcast_block((self.clone(), transactions), state.clone().miners).await;
+        broadcast_block((self.clone(), transactions.clone()), state.clone().miners).await;
+        BLOCK_BROADCASTER.broadcast((self.clone(), transactions));
         WEB_SOCKET_BROADCASTER
             .broadcast(
                 self.number as u32,
                 state.miners.first().unwrap().host.clone(),
             )
             .await;
+        Ok(())
     }
 
     pub fn current_block_number() -> u32 {
+        Self::last_sealed_block_number().unwrap_or(0)
+    }
+
+    pub fn first_block_number() -> Option<u32> {
+        blocks::dsl::blocks
+            .select(min(blocks::dsl::number))
+            .first::<Option<i32>>(&get_pg_connection())
+            .unwrap()
+            .map(|n: i32| n as u32)
+    }
+
+    pub fn last_sealed_block_number() -> Option<u32> {
         let pg_db = get_pg_connection();
         blocks::dsl::blocks
             .select(max(blocks::dsl::number))
@@ -144,6 +309,162 @@ impl Block {
             .first::<Option<i32>>(&pg_db)
             .unwrap()
             .map(|n: i32| n as u32)
-            .unwrap_or(0)
+    }
+}
+
+fn is_valid_seal(transaction: &Transaction, current_miner: &Miner) -> bool {
+    let arguments: Vec<serde_cbor::Value> = match serde_cbor::from_slice(&transaction.arguments) {
+        Ok(arguments) => arguments,
+        Err(_) => return false,
+    };
+    let value = match &arguments[..] {
+        [value] => serde_cbor::value::from_value::<[u8; 32]>(value.clone()),
+        _ => return false,
+    };
+    transaction.contract == *TOKEN_CONTRACT
+        && transaction.function == "seal"
+        && transaction.sender == current_miner.address.to_vec()
+        && value
+            .map(|value| sha256(value.to_vec()) == current_miner.hash_onion_skin)
+            .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransactionRequest;
+    use ellipticoin_test_framework::constants::actors::{ALICE, ALICES_PRIVATE_KEY, BOB};
+    use std::env;
+
+    const SKIN: [u8; 32] = [1; 32];
+
+    fn miner() -> Miner {
+        Miner {
+            host: "localhost".to_string(),
+            address: *ALICE,
+            burn_per_block: 0,
+            hash_onion_skin: sha256(SKIN.to_vec()),
+        }
+    }
+
+    fn signed(mut transaction: Transaction) -> Transaction {
+        transaction.raw = TransactionRequest::from(transaction.clone()).sign();
+        transaction
+    }
+
+    fn seal_transaction(skin: [u8; 32]) -> Transaction {
+        signed(Transaction {
+            network_id: network_id() as i64,
+            contract: TOKEN_CONTRACT.clone(),
+            sender: ALICE.to_vec(),
+            function: "seal".to_string(),
+            arguments: serde_cbor::to_vec(&vec![bytes_to_value(skin.to_vec())]).unwrap(),
+            ..Default::default()
+        })
+    }
+
+    fn block(number: i32) -> Block {
+        Block {
+            number,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        assert!(block(0)
+            .validate(*ALICE, &[seal_transaction(SKIN)], &miner(), 0)
+            .is_ok());
+        assert!(block(2)
+            .validate(*ALICE, &[seal_transaction(SKIN)], &miner(), 2)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_validate_invalid_signer() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        assert_eq!(
+            block(0)
+                .validate(*BOB, &[seal_transaction(SKIN)], &miner(), 0)
+                .unwrap_err()
+                .message,
+            INVALID_BLOCK_SIGNER.message
+        );
+    }
+
+    #[test]
+    fn test_validate_invalid_block_number() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        assert_eq!(
+            block(1)
+                .validate(*ALICE, &[seal_transaction(SKIN)], &miner(), 0)
+                .unwrap_err()
+                .message,
+            INVALID_BLOCK_NUMBER.message
+        );
+        assert_eq!(
+            block(1)
+                .validate(*ALICE, &[seal_transaction(SKIN)], &miner(), 2)
+                .unwrap_err()
+                .message,
+            INVALID_BLOCK_NUMBER.message
+        );
+    }
+
+    #[test]
+    fn test_validate_invalid_seal_transaction() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        assert_eq!(
+            block(0)
+                .validate(*ALICE, &[], &miner(), 0)
+                .unwrap_err()
+                .message,
+            INVALID_SEAL_TRANSACTION.message
+        );
+        assert_eq!(
+            block(0)
+                .validate(*ALICE, &[seal_transaction([2; 32])], &miner(), 0)
+                .unwrap_err()
+                .message,
+            INVALID_SEAL_TRANSACTION.message
+        );
+    }
+
+    #[test]
+    fn test_validate_invalid_network_id() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let transaction = signed(Transaction {
+            network_id: network_id() as i64 + 1,
+            sender: ALICE.to_vec(),
+            arguments: serde_cbor::to_vec(&Vec::<serde_cbor::Value>::new()).unwrap(),
+            ..Default::default()
+        });
+        assert_eq!(
+            block(0)
+                .validate(*ALICE, &[transaction, seal_transaction(SKIN)], &miner(), 0)
+                .unwrap_err()
+                .message,
+            INVALID_NETWORK_ID.message
+        );
+    }
+
+    #[test]
+    fn test_validate_sender_is_not_signer() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut transaction = signed(Transaction {
+            network_id: network_id() as i64,
+            sender: ALICE.to_vec(),
+            arguments: serde_cbor::to_vec(&Vec::<serde_cbor::Value>::new()).unwrap(),
+            ..Default::default()
+        });
+        transaction.nonce += 1;
+        assert_eq!(
+            block(0)
+                .validate(*ALICE, &[transaction, seal_transaction(SKIN)], &miner(), 0)
+                .unwrap_err()
+                .message,
+            SENDER_IS_NOT_SIGNER.message
+        );
     }
 }
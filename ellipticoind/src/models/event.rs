@@ -0,0 +1,111 @@
+use crate::{
+    config::get_pg_connection,
+    constants::MAX_EVENTS_PAGE_SIZE,
+    diesel::{ExpressionMethods, PgArrayExpressionMethods, QueryDsl, RunQueryDsl},
+    models::Transaction,
+    schema::{events, events::dsl},
+};
+use diesel::dsl::insert_into;
+
+#[derive(Queryable, Identifiable, Associations, PartialEq, Clone, Debug)]
+#[belongs_to(Transaction)]
+#[table_name = "events"]
+pub struct Event {
+    pub id: i32,
+    pub transaction_id: i32,
+    pub contract: String,
+    pub name: String,
+    pub addresses: Vec<Vec<u8>>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Insertable)]
+#[table_name = "events"]
+pub struct NewEvent {
+    pub transaction_id: i32,
+    pub contract: String,
+    pub name: String,
+    pub addresses: Vec<Vec<u8>>,
+    pub data: Vec<u8>,
+}
+
+impl Event {
+    pub fn insert(transaction_id: i32, events: Vec<ellipticoin::Event>) {
+        let events = events
+            .into_iter()
+            .map(|event| NewEvent {
+                transaction_id,
+                contract: event.contract,
+                name: event.name,
+                addresses: event.addresses.into_iter().map(Into::into).collect(),
+                data: event.data,
+            })
+            .collect::<Vec<NewEvent>>();
+        if events.is_empty() {
+            return;
+        }
+        insert_into(dsl::events)
+            .values(&events)
+            .execute(&get_pg_connection())
+            .unwrap();
+    }
+
+    pub fn for_transaction(transaction_id: i32) -> Vec<Event> {
+        dsl::events
+            .filter(dsl::transaction_id.eq(transaction_id))
+            .order(dsl::id.asc())
+            .load::<Event>(&get_pg_connection())
+            .unwrap()
+    }
+
+    // Returns up to `first` events, capped at MAX_EVENTS_PAGE_SIZE, with ids
+    // greater than `after`.
+    pub fn filter(
+        contract: Option<String>,
+        name: Option<String>,
+        address: Option<Vec<u8>>,
+        first: Option<u32>,
+        after: Option<u32>,
+    ) -> Vec<Event> {
+        let mut query = dsl::events.into_boxed();
+        if let Some(after) = after {
+            query = query.filter(dsl::id.gt(after as i32));
+        }
+        if let Some(contract) = contract {
+            query = query.filter(dsl::contract.eq(contract));
+        }
+        if let Some(name) = name {
+            query = query.filter(dsl::name.eq(name));
+        }
+        if let Some(address) = address {
+            query = query.filter(dsl::addresses.contains(vec![address]));
+        }
+        query
+            .order(dsl::id.asc())
+            .limit(page_size(first) as i64)
+            .load::<Event>(&get_pg_connection())
+            .unwrap()
+    }
+}
+
+fn page_size(first: Option<u32>) -> u32 {
+    first.map_or(*MAX_EVENTS_PAGE_SIZE, |first| {
+        first.min(*MAX_EVENTS_PAGE_SIZE)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_size() {
+        assert_eq!(page_size(None), *MAX_EVENTS_PAGE_SIZE);
+        assert_eq!(page_size(Some(10)), 10);
+        assert_eq!(page_size(Some(0)), 0);
+        assert_eq!(
+            page_size(Some(*MAX_EVENTS_PAGE_SIZE + 1)),
+            *MAX_EVENTS_PAGE_SIZE
+        );
+    }
+}
@@ -1,7 +1,13 @@
 pub mod block;
+pub mod event;
 pub mod hash_onion;
+pub mod state_change;
+pub mod state_version;
 pub mod transaction;
 
 pub use block::*;
+pub use event::*;
 pub use hash_onion::*;
+pub use state_change::*;
+pub use state_version::*;
 pub use transaction::*;
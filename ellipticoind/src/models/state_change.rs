@@ -0,0 +1,56 @@
+use crate::{
+    config::get_pg_connection,
+    diesel::{ExpressionMethods, QueryDsl, RunQueryDsl},
+    models::Transaction,
+    schema::{state_changes, state_changes::dsl},
+};
+use diesel::dsl::insert_into;
+
+#[derive(Queryable, Identifiable, Associations, PartialEq, Clone, Debug)]
+#[belongs_to(Transaction)]
+#[table_name = "state_changes"]
+pub struct StateChange {
+    pub id: i32,
+    pub transaction_id: i32,
+    pub key: Vec<u8>,
+    pub old_value: Vec<u8>,
+    pub new_value: Vec<u8>,
+}
+
+#[derive(Insertable)]
+#[table_name = "state_changes"]
+pub struct NewStateChange {
+    pub transaction_id: i32,
+    pub key: Vec<u8>,
+    pub old_value: Vec<u8>,
+    pub new_value: Vec<u8>,
+}
+
+impl StateChange {
+    pub fn insert(transaction_id: i32, state_changes: Vec<(Vec<u8>, Vec<u8>, Vec<u8>)>) {
+        let state_changes = state_changes
+            .into_iter()
+            .map(|(key, old_value, new_value)| NewStateChange {
+                transaction_id,
+                key,
+                old_value,
+                new_value,
+            })
+            .collect::<Vec<NewStateChange>>();
+        if state_changes.is_empty() {
+            return;
+        }
+        insert_into(dsl::state_changes)
+            .values(&state_changes)
+            .execute(&get_pg_connection())
+            .unwrap();
+    }
+
+    pub fn for_transaction(transaction_id: i32) -> Vec<StateChange> {
+        dsl::state_changes
+            .filter(dsl::transaction_id.eq(transaction_id))
+            .order(dsl::id.asc())
+            .load::<StateChange>(&get_pg_connection())
+            .unwrap()
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    config::{get_pg_connection, OPTS},
+    diesel::{ExpressionMethods, QueryDsl, RunQueryDsl},
+    schema::{state_versions, state_versions::dsl},
+    storage::Storage,
+};
+use diesel::{dsl::insert_into, OptionalExtension, PgConnection};
+
+#[derive(Queryable, Insertable, Clone, Debug)]
+#[table_name = "state_versions"]
+pub struct StateVersion {
+    pub block_number: i32,
+    pub key: Vec<u8>,
+    pub previous_value: Vec<u8>,
+    pub value: Vec<u8>,
+}
+
+impl StateVersion {
+    pub fn insert(pg_db: &PgConnection, block_number: i32, storage: &Storage) {
+        let state_versions = storage
+            .block_state
+            .iter()
+            .map(|(key, value)| StateVersion {
+                block_number,
+                key: key.clone(),
+                previous_value: storage.committed_get(key),
+                value: value.clone(),
+            })
+            .filter(|state_version| state_version.previous_value != state_version.value)
+            .collect::<Vec<StateVersion>>();
+        for chunk in state_versions.chunks(10_000) {
+            insert_into(dsl::state_versions)
+                .values(chunk)
+                .execute(pg_db)
+                .unwrap();
+        }
+    }
+
+    pub fn prune(block_number: i32) {
+        if let Some(state_retention) = OPTS.state_retention {
+            diesel::delete(
+                dsl::state_versions
+                    .filter(dsl::block_number.lt(block_number - state_retention as i32)),
+            )
+            .execute(&get_pg_connection())
+            .unwrap();
+        }
+    }
+
+    pub fn is_pruned(block_number: u32, current_block_number: u32) -> bool {
+        OPTS.state_retention
+            .map(|state_retention| block_number + state_retention < current_block_number)
+            .unwrap_or(false)
+    }
+
+    pub fn since(block_number: u32) -> Vec<StateVersion> {
+        dsl::state_versions
+            .filter(dsl::block_number.gt(block_number as i32))
+            .order(dsl::block_number.desc())
+            .load::<StateVersion>(&get_pg_connection())
+            .unwrap()
+    }
+
+    // Returns the value each key starting with `prefix` had before it was
+    // first changed after `block_number`
+    pub fn previous_values_since(prefix: &[u8], block_number: u32) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut query = dsl::state_versions
+            .select((dsl::key, dsl::previous_value))
+            .filter(dsl::block_number.gt(block_number as i32))
+            .filter(dsl::key.ge(prefix))
+            .order((dsl::key.asc(), dsl::block_number.asc()))
+            .distinct_on(dsl::key)
+            .into_boxed();
+        if let Some(upper_bound) = prefix_upper_bound(prefix) {
+            query = query.filter(dsl::key.lt(upper_bound));
+        }
+        query
+            .load::<(Vec<u8>, Vec<u8>)>(&get_pg_connection())
+            .unwrap()
+    }
+
+    // Returns `None` if the current value of `key` is also its value at `block_number`
+    pub fn value_at(key: &[u8], block_number: u32) -> Option<Vec<u8>> {
+        let pg_db = get_pg_connection();
+        dsl::state_versions
+            .select(dsl::value)
+            .filter(dsl::key.eq(key))
+            .filter(dsl::block_number.le(block_number as i32))
+            .order(dsl::block_number.desc())
+            .first::<Vec<u8>>(&pg_db)
+            .optional()
+            .unwrap()
+            .or_else(|| {
+                dsl::state_versions
+                    .select(dsl::previous_value)
+                    .filter(dsl::key.eq(key))
+                    .filter(dsl::block_number.gt(block_number as i32))
+                    .order(dsl::block_number.asc())
+                    .first::<Vec<u8>>(&pg_db)
+                    .optional()
+                    .unwrap()
+            })
+    }
+}
+
+// The smallest key greater than every key starting with `prefix`
+fn prefix_upper_bound(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut upper_bound = prefix.to_vec();
+    while let Some(byte) = upper_bound.pop() {
+        if byte < u8::MAX {
+            upper_bound.push(byte + 1);
+            return Some(upper_bound);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_upper_bound() {
+        assert_eq!(prefix_upper_bound(&[1, 2]), Some(vec![1, 3]));
+        assert_eq!(prefix_upper_bound(&[1, 255]), Some(vec![2]));
+        assert_eq!(prefix_upper_bound(&[255, 255]), None);
+        assert_eq!(prefix_upper_bound(&[]), None);
+    }
+}
@@ -1,21 +1,21 @@
 use crate::{
+    api::helpers::validate_transaction,
     config::get_pg_connection,
-    diesel::{ExpressionMethods, RunQueryDsl},
-    models::block::Block,
-    schema::{
-        transactions,
-        transactions::{
-            columns::{nonce, sender},
-            dsl::transactions as transactions_table,
-        },
+    diesel::RunQueryDsl,
+    models::{block::Block, Event, StateChange},
+    schema::{transactions, transactions::dsl::transactions as transactions_table},
+    state::STATE,
+    system_contracts::{
+        self,
+        api::InMemoryAPI,
+        ellipticoin::{self, InvalidNonce},
     },
-    state::IN_MEMORY_STATE,
-    system_contracts::{self, api::InMemoryAPI},
     transaction::TransactionRequest,
 };
-use diesel::{insert_into, OptionalExtension, QueryDsl};
+use ::ellipticoin::Address;
+use diesel::insert_into;
 use serde::{Deserialize, Serialize};
-use serde_cbor::from_slice;
+use serde_cbor::{from_slice, value::from_value, Value};
 use std::{convert::TryInto, str};
 
 #[derive(
@@ -44,6 +44,7 @@ pub struct Transaction {
     pub arguments: Vec<u8>,
     pub return_value: Vec<u8>,
     pub raw: Vec<u8>,
+    pub hash: Option<Vec<u8>>,
 }
 
 #[derive(Insertable, Default)]
@@ -59,6 +60,7 @@ pub struct NewTransaction {
     pub arguments: Vec<u8>,
     pub return_value: Vec<u8>,
     pub raw: Vec<u8>,
+    pub hash: Option<Vec<u8>>,
 }
 
 #[derive(Serialize, Debug)]
@@ -76,21 +78,37 @@ impl Transaction {
     pub async fn run(
         current_block: &Block,
         transaction_request: TransactionRequest,
+        raw: Vec<u8>,
         position: i32,
     ) -> Self {
-        let mut state = IN_MEMORY_STATE.lock().await;
+        let mut state = STATE.lock().await;
         let mut api = InMemoryAPI::new(&mut state, Some(transaction_request.clone()));
-        let return_value = system_contracts::run(&mut api, transaction_request.clone());
-        Transaction::insert(transaction_request, current_block, position, return_value)
+        let return_value = system_contracts::run_in_block(
+            &mut api,
+            transaction_request.clone(),
+            current_block.number as u32,
+        );
+        let transaction = Transaction::insert(
+            transaction_request,
+            raw,
+            current_block,
+            position,
+            return_value,
+        );
+        StateChange::insert(transaction.id, api.state_changes);
+        Event::insert(transaction.id, api.events);
+        transaction
     }
 
     pub fn insert(
         transaction_request: TransactionRequest,
+        raw: Vec<u8>,
         current_block: &Block,
         position: i32,
         return_value: serde_cbor::Value,
     ) -> Self {
         let mut completed_transaction = NewTransaction {
+            hash: hash(&transaction_request, &return_value),
             network_id: transaction_request.network_id as i64,
             block_number: current_block.number,
             sender: transaction_request.sender[..].try_into().unwrap(),
@@ -99,6 +117,7 @@ impl Transaction {
             function: transaction_request.function,
             nonce: transaction_request.nonce as i32,
             return_value: serde_cbor::to_vec(&return_value).unwrap(),
+            raw,
             ..Default::default()
         };
         completed_transaction.position = position;
@@ -117,12 +136,30 @@ impl Transaction {
             nonce: completed_transaction.nonce,
             position: completed_transaction.position,
             raw: completed_transaction.raw,
+            hash: completed_transaction.hash,
             return_value: completed_transaction.return_value,
             sender: completed_transaction.sender,
         }
     }
 }
 
+impl Transaction {
+    pub fn is_signed_by_sender(&self) -> bool {
+        validate_transaction(&self.raw)
+            .map(|transaction_request| transaction_request == TransactionRequest::from(self))
+            .unwrap_or(false)
+    }
+}
+
+// Transactions rejected for their nonce, such as replays, aren't given a hash
+// so they can't be mistaken for the transaction they replayed.
+fn hash(transaction_request: &TransactionRequest, return_value: &Value) -> Option<Vec<u8>> {
+    match from_value::<Result<Value, InvalidNonce>>(return_value.clone()) {
+        Ok(Err(_)) => None,
+        _ => Some(transaction_request.hash().to_vec()),
+    }
+}
+
 impl From<Transaction> for TransactionWithoutHash {
     fn from(transaction: Transaction) -> Self {
         Self {
@@ -150,15 +187,97 @@ impl From<&Transaction> for TransactionRequest {
     }
 }
 
-pub fn next_nonce(address: Vec<u8>) -> u32 {
-    let pg_db = get_pg_connection();
-    transactions::dsl::transactions
-        .order(nonce.desc())
-        .filter(sender.eq(address))
-        .select(nonce)
-        .first(&pg_db)
-        .optional()
-        .unwrap()
-        .unwrap_or(1) as u32
-        + 1
+pub async fn next_nonce(address: [u8; 32]) -> u32 {
+    let mut state = STATE.lock().await;
+    let mut api = InMemoryAPI::new(&mut state, None);
+    ellipticoin::get_nonce(&mut api, Address::PublicKey(address)) + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_zebra::SigningKey;
+    use ellipticoin_test_framework::constants::actors::{
+        ALICE, ALICES_PRIVATE_KEY, BOBS_PRIVATE_KEY,
+    };
+    use std::convert::TryFrom;
+
+    fn signed_transaction(private_key: [u8; 64]) -> Transaction {
+        let mut transaction = Transaction {
+            contract: "Token".to_string(),
+            sender: ALICE.to_vec(),
+            nonce: 1,
+            function: "transfer".to_string(),
+            arguments: serde_cbor::to_vec(&Vec::<serde_cbor::Value>::new()).unwrap(),
+            ..Default::default()
+        };
+        transaction.raw = TransactionRequest::from(&transaction).sign_with(SigningKey::from(
+            <[u8; 32]>::try_from(&private_key[..32]).unwrap(),
+        ));
+        transaction
+    }
+
+    #[test]
+    fn test_hash() {
+        let transaction_request = TransactionRequest::from(&signed_transaction(ALICES_PRIVATE_KEY));
+        let return_value: Result<(), wasm_rpc::error::Error> = Err(wasm_rpc::error::Error {
+            code: 1,
+            message: "Insufficient funds".to_string(),
+        });
+        assert_eq!(
+            hash(
+                &transaction_request,
+                &serde_cbor::value::to_value(return_value).unwrap()
+            ),
+            Some(transaction_request.hash().to_vec())
+        );
+        assert_eq!(
+            hash(
+                &transaction_request,
+                &serde_cbor::value::to_value(Ok::<(), ()>(())).unwrap()
+            ),
+            Some(transaction_request.hash().to_vec())
+        );
+    }
+
+    #[test]
+    fn test_hash_of_rejected_transaction() {
+        let transaction_request = TransactionRequest::from(&signed_transaction(ALICES_PRIVATE_KEY));
+        let return_value: Result<(), InvalidNonce> = Err(InvalidNonce {
+            code: 7,
+            message: "Invalid nonce: expected 2".to_string(),
+            expected_nonce: 2,
+        });
+        assert_eq!(
+            hash(
+                &transaction_request,
+                &serde_cbor::value::to_value(return_value).unwrap()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_signed_by_sender() {
+        assert!(signed_transaction(ALICES_PRIVATE_KEY).is_signed_by_sender());
+    }
+
+    #[test]
+    fn test_is_signed_by_sender_with_another_key() {
+        assert!(!signed_transaction(BOBS_PRIVATE_KEY).is_signed_by_sender());
+    }
+
+    #[test]
+    fn test_is_signed_by_sender_with_modified_transaction() {
+        let mut transaction = signed_transaction(ALICES_PRIVATE_KEY);
+        transaction.nonce += 1;
+        assert!(!transaction.is_signed_by_sender());
+    }
+
+    #[test]
+    fn test_is_signed_by_sender_without_raw() {
+        let mut transaction = signed_transaction(ALICES_PRIVATE_KEY);
+        transaction.raw = vec![];
+        assert!(!transaction.is_signed_by_sender());
+    }
 }
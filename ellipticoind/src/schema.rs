@@ -4,6 +4,18 @@ table! {
         memory_changeset_hash -> Bytea,
         storage_changeset_hash -> Bytea,
         sealed -> Bool,
+        state_root -> Bytea,
+    }
+}
+
+table! {
+    events (id) {
+        id -> Int4,
+        transaction_id -> Int4,
+        contract -> Varchar,
+        name -> Varchar,
+        addresses -> Array<Bytea>,
+        data -> Bytea,
     }
 }
 
@@ -14,6 +26,25 @@ table! {
     }
 }
 
+table! {
+    state_changes (id) {
+        id -> Int4,
+        transaction_id -> Int4,
+        key -> Bytea,
+        old_value -> Bytea,
+        new_value -> Bytea,
+    }
+}
+
+table! {
+    state_versions (block_number, key) {
+        block_number -> Int4,
+        key -> Bytea,
+        previous_value -> Bytea,
+        value -> Bytea,
+    }
+}
+
 table! {
     transactions (id) {
         id -> Int4,
@@ -27,9 +58,19 @@ table! {
         arguments -> Bytea,
         return_value -> Bytea,
         raw -> Bytea,
+        hash -> Nullable<Bytea>,
     }
 }
 
+joinable!(events -> transactions (transaction_id));
+joinable!(state_changes -> transactions (transaction_id));
 joinable!(transactions -> blocks (block_number));
 
-allow_tables_to_appear_in_same_query!(blocks, hash_onion, transactions,);
+allow_tables_to_appear_in_same_query!(
+    blocks,
+    events,
+    hash_onion,
+    state_changes,
+    state_versions,
+    transactions,
+);
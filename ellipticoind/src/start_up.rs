@@ -5,21 +5,29 @@ use crate::{
     diesel::{ExpressionMethods, QueryDsl, RunQueryDsl},
     helpers::{bytes_to_value, run_transaction},
     legacy, models,
-    models::{Block, HashOnion, Transaction},
+    models::{Block, HashOnion, StateVersion, Transaction},
     schema::{blocks::dsl as blocks_dsl, transactions::dsl as transactions_dsl},
     serde_cbor::Deserializer,
-    state::{is_mining, IN_MEMORY_STATE},
+    state::{is_mining, STATE},
     static_files::STATIC_FILES,
-    system_contracts::api::InMemoryAPI,
+    system_contracts::{api::InMemoryAPI, ellipticoin::get_block_number, token},
     transaction::TransactionRequest,
 };
 use diesel::{
     delete,
     dsl::{exists, not},
-    sql_query,
+    insert_into, sql_query,
 };
+use ellipticoin::StateAPI;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fs::File, path::Path};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+const CBOR_MAP_MAJOR_TYPE: u8 = 5;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct VMState {
@@ -41,27 +49,51 @@ pub async fn start_miner() {
             (*BURN_PER_BLOCK).into(),
             bytes_to_value(skin),
         ],
-    );
+    )
+    .await;
     if *GENESIS_NODE {
         let block = Block::insert(0);
-        models::Transaction::run(&block, start_mining_transaction, 0).await;
+        models::Transaction::run(
+            &block,
+            start_mining_transaction.clone(),
+            start_mining_transaction.sign(),
+            0,
+        )
+        .await;
         println!("Won block #0");
-        block.seal(1).await;
+        block.seal(1).await.expect("Failed to seal block #0");
     } else {
-        run_transaction(start_mining_transaction).await;
+        run_transaction(start_mining_transaction)
+            .await
+            .expect("Failed to start mining");
     }
 }
 
 pub async fn catch_up() {
     let pg_db = get_pg_connection();
     let mut won_blocks = 0;
-    for block_number in 0.. {
+    let next_block_number = Block::last_sealed_block_number()
+        .map(|block_number| block_number + 1)
+        .unwrap_or(0);
+    for block_number in next_block_number.. {
         if let Ok((block, transactions)) = get_block(block_number).await {
             if !block.sealed {
                 break;
             }
-
-            let state = block.apply(transactions).await;
+            if !has_valid_signatures(&block, &transactions) {
+                println!(
+                    "Block #{} contains a transaction that wasn't signed by its sender",
+                    block_number
+                );
+                break;
+            }
+            let state = match block.apply(transactions).await {
+                Ok(state) => state,
+                Err(error) => {
+                    println!("Failed to apply block #{}: {}", block_number, error.message);
+                    break;
+                }
+            };
             if state.miners.first().unwrap().address == verification_key() {
                 won_blocks += 1;
             }
@@ -76,6 +108,14 @@ pub async fn catch_up() {
     println!("Syncing complete");
 }
 
+// Transactions in blocks below ACTIVATION_HEIGHT were stored without their
+// signed bytes.
+fn has_valid_signatures(block: &Block, transactions: &[Transaction]) -> bool {
+    transactions.iter().all(|transaction| {
+        !block.must_verify(&transaction.raw) || transaction.is_signed_by_sender()
+    })
+}
+
 pub async fn download_static_files() {
     let static_dir = Path::new("ellipticoind/static");
     for (file_name, hash) in STATIC_FILES.iter() {
@@ -86,9 +126,21 @@ pub async fn download_static_files() {
 }
 pub async fn reset_state() {
     download_static_files().await;
+    if OPTS.save_state && persisted_state_is_current().await {
+        remove_unsealed_blocks();
+        println!("Resuming from block #{}", Block::current_block_number());
+        return;
+    }
+    if let Some(state_snapshot_path) = &OPTS.state_snapshot_path {
+        reset_pg().await;
+        HashOnion::generate().await;
+        load_state_snapshot(state_snapshot_path).await;
+        return;
+    }
     load_genesis_state().await;
 
     if OPTS.save_state {
+        remove_unsealed_blocks();
         run_transactions_in_db().await;
     } else {
         reset_pg().await;
@@ -96,19 +148,104 @@ pub async fn reset_state() {
     }
 }
 
+async fn persisted_state_is_current() -> bool {
+    let mut state = STATE.lock().await;
+    if state.is_empty() {
+        return false;
+    }
+    let mut api = InMemoryAPI::new(&mut state, None);
+    get_block_number(&mut api) == Block::current_block_number() + 1
+}
+
+fn remove_unsealed_blocks() {
+    let pg_db = get_pg_connection();
+    delete(transactions_dsl::transactions)
+        .filter(
+            transactions_dsl::block_number.eq_any(
+                blocks_dsl::blocks
+                    .select(blocks_dsl::number)
+                    .filter(blocks_dsl::sealed.eq(false)),
+            ),
+        )
+        .execute(&pg_db)
+        .unwrap();
+    delete(blocks_dsl::blocks)
+        .filter(blocks_dsl::sealed.eq(false))
+        .execute(&pg_db)
+        .unwrap();
+}
+
 pub async fn load_genesis_state() {
-    let mut state = IN_MEMORY_STATE.lock().await;
+    let mut state = STATE.lock().await;
+    state.clear();
     let genesis_file = File::open(OPTS.genesis_state_path.clone()).expect(&format!(
         "Genesis file {} not found",
         &OPTS.genesis_state_path
     ));
 
-    for (key, value) in Deserializer::from_reader(&genesis_file)
-        .into_iter::<(Vec<u8>, Vec<u8>)>()
-        .map(Result::unwrap)
-    {
-        state.insert(key, value);
+    let (_block, entries) = read_state_file(genesis_file);
+    for (key, value) in entries {
+        state.set(&key, &value);
     }
+    let mut api = InMemoryAPI::new(&mut state, None);
+    token::seed_metadata(&mut api);
+    api.commit();
+    state.commit();
+}
+
+pub async fn load_state_snapshot(path: &str) {
+    let mut state = STATE.lock().await;
+    state.clear();
+    let snapshot_file =
+        File::open(path).unwrap_or_else(|_| panic!("State snapshot {} not found", path));
+    let (block, entries) = read_state_file(snapshot_file);
+    let block =
+        block.unwrap_or_else(|| panic!("State snapshot {} doesn't start with a block", path));
+    for (key, value) in entries {
+        state.set(&key, &value);
+    }
+    if block.must_verify(&block.state_root) {
+        if block.state_root != state.state_root().to_vec() {
+            panic!(
+                "State snapshot doesn't match the state root of block #{}",
+                block.number
+            );
+        }
+    } else {
+        println!(
+            "Block #{} has no state root so the state snapshot can't be verified",
+            block.number
+        );
+    }
+    state.commit();
+    insert_into(blocks_dsl::blocks)
+        .values(&block)
+        .execute(&get_pg_connection())
+        .unwrap();
+    println!("Loaded state snapshot at block #{}", block.number);
+}
+
+// State files are streams of CBOR encoded `(key, value)` pairs. Snapshots
+// written by `dump-state` start with the block they were taken at.
+fn read_state_file(file: File) -> (Option<Block>, impl Iterator<Item = (Vec<u8>, Vec<u8>)>) {
+    let mut reader = BufReader::new(file);
+    let starts_with_block = reader
+        .fill_buf()
+        .unwrap()
+        .first()
+        .is_some_and(|byte| byte >> 5 == CBOR_MAP_MAJOR_TYPE);
+    let mut deserializer = Deserializer::from_reader(reader);
+    let block = if starts_with_block {
+        Some(Block::deserialize(&mut deserializer).unwrap())
+    } else {
+        None
+    };
+    (
+        block,
+        deserializer
+            .into_iter::<(Vec<u8>, Vec<u8>)>()
+            .map(Result::unwrap),
+    )
 }
 
 pub async fn run_transactions_in_db() {
@@ -120,8 +257,21 @@ pub async fn run_transactions_in_db() {
         ))
         .load::<Transaction>(&pg_db)
         .unwrap();
-    let mut state = IN_MEMORY_STATE.lock().await;
+    sql_query("TRUNCATE state_versions")
+        .execute(&pg_db)
+        .unwrap();
+    let mut state = STATE.lock().await;
+    let mut block_number = transactions
+        .first()
+        .map(|transaction| transaction.block_number);
     for mut transaction in transactions {
+        if let Some(previous_block_number) = block_number {
+            if previous_block_number != transaction.block_number {
+                StateVersion::insert(&pg_db, previous_block_number, &state);
+                state.commit();
+            }
+        }
+        block_number = Some(transaction.block_number);
         let mut api = InMemoryAPI::new(&mut state, Some(transaction.clone().into()));
         legacy::run(&mut api, &mut transaction).await;
         if transaction.id % 10000 == 0 && transaction.id != 0 {
@@ -132,6 +282,11 @@ pub async fn run_transactions_in_db() {
             )
         };
     }
+    if let Some(block_number) = block_number {
+        StateVersion::insert(&pg_db, block_number, &state);
+        StateVersion::prune(block_number);
+    }
+    state.commit();
     delete(blocks_dsl::blocks)
         .filter(not(exists(
             transactions_dsl::transactions
@@ -150,4 +305,101 @@ async fn reset_pg() {
         .execute(&pg_db)
         .unwrap();
     sql_query("TRUNCATE hash_onion").execute(&pg_db).unwrap();
+    sql_query("TRUNCATE state_versions")
+        .execute(&pg_db)
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::ACTIVATION_HEIGHT;
+    use ellipticoin_test_framework::constants::actors::{ALICE, ALICES_PRIVATE_KEY};
+    use std::env;
+
+    fn write_state_file(name: &str, block: Option<&Block>) -> File {
+        let path = env::temp_dir().join(name);
+        let mut file = File::create(&path).unwrap();
+        if let Some(block) = block {
+            serde_cbor::to_writer(&mut file, block).unwrap();
+        }
+        serde_cbor::to_writer(&mut file, &(vec![1u8], vec![2u8])).unwrap();
+        serde_cbor::to_writer(&mut file, &(vec![3u8], vec![4u8])).unwrap();
+        File::open(&path).unwrap()
+    }
+
+    fn transaction(raw: Vec<u8>) -> Transaction {
+        Transaction {
+            sender: ALICE.to_vec(),
+            arguments: serde_cbor::to_vec(&Vec::<serde_cbor::Value>::new()).unwrap(),
+            raw,
+            ..Default::default()
+        }
+    }
+
+    fn block(number: u32) -> Block {
+        Block {
+            number: number as i32,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_has_valid_signatures() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut signed_transaction = transaction(vec![]);
+        signed_transaction.raw = TransactionRequest::from(&signed_transaction).sign();
+        assert!(has_valid_signatures(
+            &block(*ACTIVATION_HEIGHT),
+            &[signed_transaction]
+        ));
+    }
+
+    #[test]
+    fn test_has_valid_signatures_without_raw() {
+        assert!(has_valid_signatures(
+            &block(*ACTIVATION_HEIGHT - 1),
+            &[transaction(vec![])]
+        ));
+        assert!(!has_valid_signatures(
+            &block(*ACTIVATION_HEIGHT),
+            &[transaction(vec![])]
+        ));
+    }
+
+    #[test]
+    fn test_has_valid_signatures_with_invalid_raw() {
+        assert!(!has_valid_signatures(
+            &block(*ACTIVATION_HEIGHT - 1),
+            &[transaction(vec![1, 2, 3])]
+        ));
+    }
+
+    #[test]
+    fn test_read_state_file() {
+        let (block, entries) = read_state_file(write_state_file("test_read_state_file", None));
+        assert!(block.is_none());
+        assert_eq!(
+            entries.collect::<Vec<_>>(),
+            vec![(vec![1], vec![2]), (vec![3], vec![4])]
+        );
+    }
+
+    #[test]
+    fn test_read_state_file_with_block() {
+        let block = Block {
+            number: 5,
+            state_root: vec![6],
+            ..Default::default()
+        };
+        let (read_block, entries) = read_state_file(write_state_file(
+            "test_read_state_file_with_block",
+            Some(&block),
+        ));
+        assert_eq!(read_block.unwrap().number, 5);
+        assert_eq!(
+            entries.collect::<Vec<_>>(),
+            vec![(vec![1], vec![2]), (vec![3], vec![4])]
+        );
+    }
 }
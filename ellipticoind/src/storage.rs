@@ -0,0 +1,294 @@
+use crate::{config::OPTS, helpers::sha256};
+use ellipticoin::merkle::{Hash, Proof, Tree};
+use rocksdb::{Direction, IteratorMode, WriteBatch, DB};
+use std::collections::{BTreeMap, HashMap};
+
+pub enum Backend {
+    Memory(HashMap<Vec<u8>, Vec<u8>>),
+    RocksDB(DB),
+}
+
+pub struct Storage {
+    pub backend: Backend,
+    pub block_state: HashMap<Vec<u8>, Vec<u8>>,
+    pub tree: Tree,
+    pub checkpoints: Vec<HashMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl Storage {
+    pub fn new() -> Self {
+        if cfg!(test) || OPTS.in_memory_state {
+            Self::memory()
+        } else {
+            Self::rocksdb(&OPTS.rocksdb_path)
+        }
+    }
+
+    pub fn memory() -> Self {
+        Self {
+            backend: Backend::Memory(HashMap::new()),
+            block_state: HashMap::new(),
+            tree: Tree::new(),
+            checkpoints: vec![],
+        }
+    }
+
+    pub fn rocksdb(path: &str) -> Self {
+        let db = DB::open_default(path)
+            .unwrap_or_else(|error| panic!("Failed to open RocksDB at {}: {}", path, error));
+        let mut tree = Tree::new();
+        for (key, value) in db.iterator(IteratorMode::Start) {
+            update_leaf(&mut tree, &key, &value);
+        }
+        Self {
+            backend: Backend::RocksDB(db),
+            block_state: HashMap::new(),
+            tree,
+            checkpoints: vec![],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.block_state.is_empty()
+            && match &self.backend {
+                Backend::Memory(state) => state.is_empty(),
+                Backend::RocksDB(db) => db.iterator(IteratorMode::Start).next().is_none(),
+            }
+    }
+
+    // Iterates over committed entries in key order
+    pub fn committed_iter<'a>(&'a self) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a> {
+        match &self.backend {
+            Backend::Memory(state) => Box::new(
+                state
+                    .clone()
+                    .into_iter()
+                    .collect::<BTreeMap<_, _>>()
+                    .into_iter(),
+            ),
+            Backend::RocksDB(db) => Box::new(
+                db.iterator(IteratorMode::Start)
+                    .map(|(key, value)| (key.to_vec(), value.to_vec())),
+            ),
+        }
+    }
+
+    pub fn changeset_hash(&self) -> Vec<u8> {
+        let mut changeset = self.block_state.iter().collect::<Vec<_>>();
+        changeset.sort();
+        sha256(serde_cbor::to_vec(&changeset).unwrap()).to_vec()
+    }
+
+    pub fn state_root(&self) -> Hash {
+        let mut tree = self.tree.clone();
+        for (key, value) in &self.block_state {
+            update_leaf(&mut tree, key, value);
+        }
+        tree.root()
+    }
+
+    pub fn committed_state_root(&self) -> Hash {
+        self.tree.root()
+    }
+
+    pub fn committed_get(&self, key: &[u8]) -> Vec<u8> {
+        match &self.backend {
+            Backend::Memory(state) => state.get(key).cloned().unwrap_or(vec![]),
+            Backend::RocksDB(db) => db.get(key).unwrap().unwrap_or(vec![]),
+        }
+    }
+
+    pub fn committed_scan(&self, prefix: &[u8]) -> BTreeMap<Vec<u8>, Vec<u8>> {
+        match &self.backend {
+            Backend::Memory(state) => state
+                .iter()
+                .filter(|(key, _value)| key.starts_with(prefix))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            Backend::RocksDB(db) => db
+                .iterator(IteratorMode::From(prefix, Direction::Forward))
+                .take_while(|(key, _value)| key.starts_with(prefix))
+                .map(|(key, value)| (key.to_vec(), value.to_vec()))
+                .collect(),
+        }
+    }
+
+    pub fn prove(&self, key: &[u8]) -> (Vec<u8>, Proof) {
+        let value = self.committed_get(key);
+        (value, self.tree.prove(&sha256(key.to_vec())))
+    }
+
+    pub fn clear(&mut self) {
+        self.block_state.clear();
+        self.tree = Tree::new();
+        match &mut self.backend {
+            Backend::Memory(state) => state.clear(),
+            Backend::RocksDB(db) => {
+                let mut batch = WriteBatch::default();
+                for (key, _value) in db.iterator(IteratorMode::Start) {
+                    batch.delete(key);
+                }
+                db.write(batch).unwrap();
+            }
+        }
+    }
+}
+
+impl ellipticoin::StateAPI for Storage {
+    fn get(&mut self, key: &[u8]) -> Vec<u8> {
+        if let Some(value) = self.block_state.get(key) {
+            return value.clone();
+        }
+        self.committed_get(key)
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.block_state.insert(key.to_vec(), value.to_vec());
+    }
+
+    fn scan(&mut self, prefix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut entries = self.committed_scan(prefix);
+        entries.extend(
+            self.block_state
+                .iter()
+                .filter(|(key, _value)| key.starts_with(prefix))
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+        entries
+            .into_iter()
+            .filter(|(_key, value)| !value.is_empty())
+            .collect()
+    }
+
+    fn commit(&mut self) {
+        self.checkpoints.clear();
+        for (key, value) in &self.block_state {
+            update_leaf(&mut self.tree, key, value);
+        }
+        match &mut self.backend {
+            Backend::Memory(state) => state.extend(self.block_state.drain()),
+            Backend::RocksDB(db) => {
+                let mut batch = WriteBatch::default();
+                for (key, value) in self.block_state.drain() {
+                    batch.put(key, value);
+                }
+                db.write(batch).unwrap();
+            }
+        }
+    }
+
+    fn revert(&mut self) {
+        self.block_state.clear();
+        self.checkpoints.clear();
+    }
+
+    fn checkpoint(&mut self) {
+        self.checkpoints.push(self.block_state.clone());
+    }
+
+    fn rollback(&mut self) {
+        if let Some(block_state) = self.checkpoints.pop() {
+            self.block_state = block_state;
+        }
+    }
+
+    fn release(&mut self) {
+        self.checkpoints.pop();
+    }
+}
+
+fn update_leaf(tree: &mut Tree, key: &[u8], value: &[u8]) {
+    if value.is_empty() {
+        tree.remove(&sha256(key.to_vec()));
+    } else {
+        tree.insert(sha256(key.to_vec()), sha256(value.to_vec()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ellipticoin::{merkle, StateAPI};
+
+    #[test]
+    fn test_commit() {
+        let mut storage = Storage::memory();
+        storage.set(&[1], &[2]);
+        assert_eq!(storage.get(&[1]), vec![2]);
+        storage.commit();
+        assert!(storage.block_state.is_empty());
+        assert_eq!(storage.get(&[1]), vec![2]);
+    }
+
+    #[test]
+    fn test_revert() {
+        let mut storage = Storage::memory();
+        storage.set(&[1], &[2]);
+        storage.commit();
+        storage.set(&[1], &[3]);
+        storage.revert();
+        assert_eq!(storage.get(&[1]), vec![2]);
+    }
+
+    #[test]
+    fn test_changeset_hash() {
+        let mut storage1 = Storage::memory();
+        storage1.set(&[1], &[2]);
+        storage1.set(&[3], &[4]);
+        let mut storage2 = Storage::memory();
+        storage2.set(&[3], &[4]);
+        storage2.set(&[1], &[2]);
+        assert_eq!(storage1.changeset_hash(), storage2.changeset_hash());
+        storage2.set(&[1], &[5]);
+        assert_ne!(storage1.changeset_hash(), storage2.changeset_hash());
+    }
+
+    #[test]
+    fn test_state_root() {
+        let mut storage = Storage::memory();
+        let empty_root = storage.state_root();
+        storage.set(&[1], &[2]);
+        let root = storage.state_root();
+        assert_ne!(root, empty_root);
+        assert_eq!(storage.committed_state_root(), empty_root);
+        storage.commit();
+        assert_eq!(storage.committed_state_root(), root);
+        storage.set(&[1], &[]);
+        storage.commit();
+        assert_eq!(storage.committed_state_root(), empty_root);
+    }
+
+    #[test]
+    fn test_prove() {
+        let mut storage = Storage::memory();
+        for i in 0..50u8 {
+            storage.set(&[i], &[i, i]);
+        }
+        storage.commit();
+        let root = storage.committed_state_root();
+        let (value, proof) = storage.prove(&[7]);
+        assert_eq!(value, vec![7, 7]);
+        assert!(merkle::verify(&root, &[7], &value, &proof));
+        assert!(!merkle::verify(&root, &[7], &[8], &proof));
+        assert!(!merkle::verify(&root, &[7], &[], &proof));
+        let (value, proof) = storage.prove(&[100]);
+        assert!(value.is_empty());
+        assert!(merkle::verify(&root, &[100], &[], &proof));
+        assert!(!merkle::verify(&root, &[100], &[1], &proof));
+    }
+
+    #[test]
+    fn test_scan() {
+        let mut storage = Storage::memory();
+        storage.set(&[1, 1], &[1]);
+        storage.set(&[1, 2], &[2]);
+        storage.set(&[2, 1], &[3]);
+        storage.commit();
+        storage.set(&[1, 0], &[4]);
+        storage.set(&[1, 2], &[]);
+        assert_eq!(
+            storage.scan(&[1]),
+            vec![(vec![1, 0], vec![4]), (vec![1, 1], vec![1])]
+        );
+    }
+}
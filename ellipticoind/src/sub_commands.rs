@@ -4,18 +4,24 @@ use crate::{
     constants::NEW_BLOCK_CHANNEL,
     diesel::{BelongingToDsl, ExpressionMethods, GroupedBy, QueryDsl, RunQueryDsl},
     miner,
-    models::{verification_key, Block, Transaction},
+    models::{verification_key, Block, StateVersion, Transaction},
     schema::{blocks::dsl as blocks_dsl, transactions::dsl as transactions_dsl},
     start_up,
-    state::get_state,
+    state::{get_state, STATE},
 };
 use async_std::task::spawn;
 use ed25519_zebra::{SigningKey, VerificationKey};
 use futures::future;
 use rand::thread_rng;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, convert::TryFrom, fs::File, str};
-use tide::listener::ListenInfo;
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    fs::File,
+    io::{BufWriter, Write},
+    str,
+};
+use tide::listener::Listener;
 
 #[derive(Serialize, Deserialize)]
 pub struct Genesis {
@@ -77,6 +83,60 @@ pub async fn dump_blocks(block_number: Option<u32>, file_name: &str) {
     .unwrap();
 }
 
+pub async fn dump_state(block_number: Option<u32>, file_name: &str) {
+    let pg_db = get_pg_connection();
+    let current_block_number = Block::current_block_number();
+    let block_number = block_number.unwrap_or(current_block_number);
+    if block_number > current_block_number {
+        println!("Block #{} has not been sealed", block_number);
+        return;
+    }
+    if StateVersion::is_pruned(block_number, current_block_number) {
+        println!("State at block #{} has been pruned", block_number);
+        return;
+    }
+    let block = blocks_dsl::blocks
+        .find(block_number as i32)
+        .first::<Block>(&pg_db)
+        .unwrap_or_else(|_| panic!("Block #{} not found", block_number));
+    // Versions are loaded newest first so each key ends up with the value it
+    // had before its first change after `block_number`
+    let mut previous_values = BTreeMap::new();
+    for state_version in StateVersion::since(block_number) {
+        previous_values.insert(state_version.key, state_version.previous_value);
+    }
+    let state = STATE.lock().await;
+    let mut file = BufWriter::new(File::create(file_name).unwrap());
+    serde_cbor::to_writer(&mut file, &block).unwrap();
+    for (key, value) in merge_sorted(state.committed_iter(), previous_values.into_iter())
+        .filter(|(_key, value)| !value.is_empty())
+    {
+        serde_cbor::to_writer(&mut file, &(key, value)).unwrap();
+    }
+    file.flush().unwrap();
+    println!("Dumped state at block #{} to {}", block_number, file_name);
+}
+
+// Merges two iterators sorted by key. Entries in `overrides` replace
+// entries in `entries` with the same key.
+fn merge_sorted<K: Ord, V>(
+    entries: impl Iterator<Item = (K, V)>,
+    overrides: impl Iterator<Item = (K, V)>,
+) -> impl Iterator<Item = (K, V)> {
+    let mut entries = entries.peekable();
+    let mut overrides = overrides.peekable();
+    std::iter::from_fn(move || match (entries.peek(), overrides.peek()) {
+        (Some((key, _)), Some((override_key, _))) if key < override_key => entries.next(),
+        (Some((key, _)), Some((override_key, _))) if key == override_key => {
+            entries.next();
+            overrides.next()
+        }
+        (_, Some(_)) => overrides.next(),
+        (Some(_), None) => entries.next(),
+        (None, None) => None,
+    })
+}
+
 pub async fn main() {
     start_up::reset_state().await;
     if !*GENESIS_NODE {
@@ -84,17 +144,33 @@ pub async fn main() {
     }
     NEW_BLOCK_CHANNEL.0.send(get_state().await).await;
     let api = api::API::new();
-    spawn(
-        api.app
-            .listen_with(socket(), |info: ListenInfo| async move {
-                println!("started listening on {}!", info.connection());
-                println!("Address: {}", base64::encode(verification_key()));
-                if *ENABLE_MINER {
-                    start_up::start_miner().await;
-                }
-                Ok(())
-            }),
-    );
+    let mut listener = api.app.bind(socket()).await.unwrap();
+    for info in listener.info() {
+        println!("started listening on {}!", info.connection());
+    }
+    println!("Address: {}", base64::encode(verification_key()));
+    spawn(async move {
+        if *ENABLE_MINER {
+            start_up::start_miner().await;
+        }
+        listener.accept().await
+    });
+    spawn(api::websocket::listen());
     spawn(miner::run());
     future::pending().await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_sorted() {
+        let entries = vec![(1, "a"), (3, "b"), (5, "c")];
+        let overrides = vec![(0, "d"), (3, ""), (4, "e"), (6, "f")];
+        assert_eq!(
+            merge_sorted(entries.into_iter(), overrides.into_iter()).collect::<Vec<_>>(),
+            vec![(0, "d"), (1, "a"), (3, ""), (4, "e"), (5, "c"), (6, "f")]
+        );
+    }
+}
@@ -1,16 +1,22 @@
-use crate::transaction::TransactionRequest;
-use ellipticoin::Address;
-use std::collections::HashMap;
+use crate::{models::StateVersion, storage::Storage, transaction::TransactionRequest};
+use ellipticoin::{Address, Event};
+use std::collections::{BTreeMap, HashMap};
 
 pub struct InMemoryAPI<'a> {
-    pub state: &'a mut HashMap<Vec<u8>, Vec<u8>>,
+    pub state: &'a mut Storage,
     pub transaction_state: HashMap<Vec<u8>, Vec<u8>>,
+    pub checkpoints: Vec<(HashMap<Vec<u8>, Vec<u8>>, usize)>,
+    pub state_changes: Vec<(Vec<u8>, Vec<u8>, Vec<u8>)>,
+    pub transaction_events: Vec<Event>,
+    pub events: Vec<Event>,
     pub transaction: TransactionRequest,
+    pub block_number: Option<u32>,
+    pub simulation: bool,
 }
 
 impl<'a> InMemoryAPI<'a> {
     pub fn new(
-        state: &'a mut async_std::sync::MutexGuard<'_, HashMap<Vec<u8>, Vec<u8>>>,
+        state: &'a mut Storage,
         transaction_request: Option<TransactionRequest>,
     ) -> InMemoryAPI<'a> {
         InMemoryAPI {
@@ -18,28 +24,111 @@ impl<'a> InMemoryAPI<'a> {
                 ..Default::default()
             }),
             transaction_state: HashMap::new(),
+            checkpoints: vec![],
+            state_changes: vec![],
+            transaction_events: vec![],
+            events: vec![],
             state,
+            block_number: None,
+            simulation: false,
+        }
+    }
+
+    // Changes committed during a simulation stay in `transaction_state` and
+    // are never written to `state`.
+    pub fn simulation(
+        state: &'a mut Storage,
+        transaction_request: TransactionRequest,
+    ) -> InMemoryAPI<'a> {
+        InMemoryAPI {
+            simulation: true,
+            ..Self::new(state, Some(transaction_request))
+        }
+    }
+
+    pub fn at_block(state: &'a mut Storage, block_number: Option<u32>) -> InMemoryAPI<'a> {
+        InMemoryAPI {
+            block_number,
+            ..Self::new(state, None)
         }
     }
 }
 impl<'a> ellipticoin::StateAPI for InMemoryAPI<'a> {
     fn get(&mut self, key: &[u8]) -> Vec<u8> {
-        self.transaction_state
-            .get(key)
-            .unwrap_or(self.state.get(key).unwrap_or(&vec![]))
-            .to_vec()
+        match self.transaction_state.get(key) {
+            Some(value) => value.to_vec(),
+            None => match self.block_number {
+                Some(block_number) => StateVersion::value_at(key, block_number)
+                    .unwrap_or_else(|| self.state.committed_get(key)),
+                None => self.state.get(key),
+            },
+        }
     }
 
     fn set(&mut self, key: &[u8], value: &[u8]) {
         self.transaction_state.insert(key.to_vec(), value.to_vec());
     }
 
+    fn scan(&mut self, prefix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut entries: BTreeMap<Vec<u8>, Vec<u8>> = match self.block_number {
+            Some(block_number) => {
+                let mut entries = self.state.committed_scan(prefix);
+                entries.extend(StateVersion::previous_values_since(prefix, block_number));
+                entries
+            }
+            None => self.state.scan(prefix).into_iter().collect(),
+        };
+        entries.extend(
+            self.transaction_state
+                .iter()
+                .filter(|(key, _value)| key.starts_with(prefix))
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+        entries
+            .into_iter()
+            .filter(|(_key, value)| !value.is_empty())
+            .collect()
+    }
+
     fn commit(&mut self) {
-        self.state.extend(self.transaction_state.clone());
+        self.checkpoints.clear();
+        let mut transaction_state = self.transaction_state.iter().collect::<Vec<_>>();
+        transaction_state.sort();
+        for (key, value) in transaction_state {
+            let previous_value = self.state.get(key);
+            if &previous_value != value {
+                self.state_changes
+                    .push((key.clone(), previous_value, value.clone()));
+            }
+            if !self.simulation {
+                self.state.set(key, value);
+            }
+        }
+        self.events.append(&mut self.transaction_events);
     }
 
     fn revert(&mut self) {
         self.transaction_state.clear();
+        self.transaction_events.clear();
+        self.checkpoints.clear();
+    }
+
+    fn checkpoint(&mut self) {
+        self.checkpoints.push((
+            self.transaction_state.clone(),
+            self.transaction_events.len(),
+        ));
+    }
+
+    fn rollback(&mut self) {
+        if let Some((transaction_state, events_len)) = self.checkpoints.pop() {
+            self.transaction_state = transaction_state;
+            self.transaction_events.truncate(events_len);
+        }
+    }
+
+    fn release(&mut self) {
+        self.checkpoints.pop();
     }
 }
 
@@ -47,4 +136,88 @@ impl<'a> ellipticoin::API for InMemoryAPI<'a> {
     fn caller(&self) -> Address {
         Address::PublicKey(self.transaction.sender.clone())
     }
+
+    fn emit_event(&mut self, event: Event) {
+        self.transaction_events.push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ellipticoin::StateAPI;
+    use ellipticoin_test_framework::constants::actors::{ALICE, ALICES_PRIVATE_KEY};
+    use std::env;
+
+    #[test]
+    fn test_commit_records_state_changes_in_key_order() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut storage = Storage::memory();
+        storage.set(&[2], &[1]);
+        let mut api = InMemoryAPI::new(&mut storage, None);
+        api.set(&[3], &[3]);
+        api.set(&[1], &[1]);
+        api.set(&[2], &[2]);
+        api.commit();
+        assert_eq!(
+            api.state_changes,
+            vec![
+                (vec![1], vec![], vec![1]),
+                (vec![2], vec![1], vec![2]),
+                (vec![3], vec![], vec![3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_commit_skips_unchanged_values() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut storage = Storage::memory();
+        storage.set(&[1], &[1]);
+        let mut api = InMemoryAPI::new(&mut storage, None);
+        api.set(&[1], &[1]);
+        api.set(&[2], &[2]);
+        api.commit();
+        assert_eq!(api.state_changes, vec![(vec![2], vec![], vec![2])]);
+        api.commit();
+        assert_eq!(api.state_changes, vec![(vec![2], vec![], vec![2])]);
+    }
+
+    #[test]
+    fn test_simulation_commit() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut storage = Storage::memory();
+        storage.set(&[1], &[1]);
+        let mut api = InMemoryAPI::simulation(&mut storage, TransactionRequest::default());
+        api.set(&[1], &[2]);
+        api.commit();
+        assert_eq!(api.get(&[1]), vec![2]);
+        assert_eq!(api.state_changes, vec![(vec![1], vec![1], vec![2])]);
+        assert_eq!(storage.get(&[1]), vec![1]);
+        assert!(storage.checkpoints.is_empty());
+    }
+
+    #[test]
+    fn test_simulation_run() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut storage = Storage::memory();
+        let transaction_request = TransactionRequest {
+            network_id: 0,
+            contract: "Ellipticoin".to_string(),
+            sender: *ALICE,
+            nonce: 1,
+            function: "harvest".to_string(),
+            arguments: vec![],
+        };
+        let mut api = InMemoryAPI::simulation(&mut storage, transaction_request.clone());
+        let return_value: Result<serde_cbor::Value, wasm_rpc::error::Error> =
+            serde_cbor::value::from_value(crate::system_contracts::run(
+                &mut api,
+                transaction_request,
+            ))
+            .unwrap();
+        assert!(return_value.is_ok());
+        assert!(!api.state_changes.is_empty());
+        assert!(storage.block_state.is_empty());
+    }
 }
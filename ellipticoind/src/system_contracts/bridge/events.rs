@@ -0,0 +1,10 @@
+use ellipticoin::{Address, Bytes, Token, VersionedU128};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct Released {
+    pub token: Token,
+    pub from: Address,
+    pub to: Bytes,
+    pub amount: VersionedU128,
+}
@@ -1,5 +1,6 @@
 pub mod constants;
 mod errors;
+mod events;
 
 use crate::system_contracts::token::{self};
 use constants::SIGNERS;
@@ -8,14 +9,14 @@ use std::{boxed::Box, convert::TryInto};
 use wasm_rpc::error::Error;
 use wasm_rpc_macros::export_native;
 
-const CONTRACT_NAME: &'static str = "Bridge";
+pub const CONTRACT_NAME: &'static str = "Bridge";
 
 export_native! {
     pub fn mint<API: ellipticoin::API>(
         api: &mut API,
         token_id: Bytes,
         address: Bytes,
-        amount: u64,
+        amount: u128,
     ) -> Result<(), Box<Error>> {
     if SIGNERS
         .iter()
@@ -28,17 +29,84 @@ export_native! {
         Ok(())
    }
 
+    pub fn set_metadata<API: ellipticoin::API>(
+        api: &mut API,
+        token_id: Bytes,
+        name: String,
+        symbol: String,
+        decimals: u8,
+        website: String,
+    ) -> Result<(), Box<Error>> {
+        validate_signer(api)?;
+        token::update_metadata(api, token(token_id), token::Metadata {
+            name,
+            symbol,
+            decimals,
+            website,
+        });
+        Ok(())
+    }
+
+    pub fn pause<API: ellipticoin::API>(api: &mut API, token_id: Bytes) -> Result<(), Box<Error>> {
+        validate_signer(api)?;
+        token::update_paused(api, token(token_id), true);
+        Ok(())
+    }
+
+    pub fn unpause<API: ellipticoin::API>(api: &mut API, token_id: Bytes) -> Result<(), Box<Error>> {
+        validate_signer(api)?;
+        token::update_paused(api, token(token_id), false);
+        Ok(())
+    }
+
+    pub fn freeze<API: ellipticoin::API>(
+        api: &mut API,
+        token_id: Bytes,
+        address: Bytes,
+    ) -> Result<(), Box<Error>> {
+        validate_signer(api)?;
+        token::update_frozen(api, token(token_id), address.try_into()?, true);
+        Ok(())
+    }
+
+    pub fn unfreeze<API: ellipticoin::API>(
+        api: &mut API,
+        token_id: Bytes,
+        address: Bytes,
+    ) -> Result<(), Box<Error>> {
+        validate_signer(api)?;
+        token::update_frozen(api, token(token_id), address.try_into()?, false);
+        Ok(())
+    }
+
     pub fn release<API: ellipticoin::API>(
         api: &mut API,
         token_id: Bytes,
-        _address: Bytes,
-        amount: u64,
+        address: Bytes,
+        amount: u128,
     ) -> Result<(), Box<Error>> {
-        token::burn(api, token(token_id),api.caller(), amount)?;
+        token::burn(api, token(token_id.clone()), api.caller(), amount)?;
+        api.emit(CONTRACT_NAME, "Released", vec![api.caller()], events::Released {
+            token: token(token_id),
+            from: api.caller(),
+            to: address,
+            amount: amount.into(),
+        });
         Ok(())
    }
 }
 
+fn validate_signer<API: ellipticoin::API>(api: &mut API) -> Result<(), Box<Error>> {
+    if SIGNERS
+        .iter()
+        .any(|&signer| Address::PublicKey(signer) == api.caller())
+    {
+        Ok(())
+    } else {
+        Err(Box::new(errors::INVALID_SIGNER.clone()))
+    }
+}
+
 pub fn token(token_id: Bytes) -> Token {
     Token {
         issuer: Address::Contract(CONTRACT_NAME.to_string()),
@@ -121,4 +189,33 @@ mod tests {
             0 * BASE_FACTOR
         );
     }
+
+    #[test]
+    fn test_pause() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, SIGNERS[0], "Token".to_string());
+        native::mint(
+            &mut api,
+            BTC.to_vec().into(),
+            Bytes::from(ALICE.to_vec()),
+            1 * BASE_FACTOR,
+        )
+        .unwrap();
+        native::pause(&mut api, BTC.to_vec().into()).unwrap();
+        assert!(token::get_paused(&mut api, token(BTC.to_vec().into())));
+        api.caller = Address::PublicKey(ALICE.clone());
+        assert!(native::unpause(&mut api, BTC.to_vec().into()).is_err());
+        assert_eq!(
+            native::release(
+                &mut api,
+                BTC.to_vec().into(),
+                Bytes(ETH_ADDRESS.to_vec()),
+                1 * BASE_FACTOR,
+            )
+            .unwrap_err()
+            .code,
+            token::errors::TOKEN_PAUSED.code
+        );
+    }
 }
@@ -17,16 +17,16 @@ lazy_static! {
         code: 4,
         message: "Insufficient allowance".to_string(),
     };
-    pub static ref BALANCE_ALREADY_UNLOCKED: Error = Error {
-        code: 5,
-        message: "Balance has already been unlocked".to_string(),
-    };
-    pub static ref BALANCE_EXCEEDS_THIS_PHASE: Error = Error {
-        code: 6,
-        message: "Only a total of 1000000 ELC can be unlocked in Phase I".to_string(),
-    };
     pub static ref MINER_IS_NOT_WHITELISTED: Error = Error {
         code: 6,
-        message: "Only a total of 1000000 ELC can be unlocked in Phase I".to_string(),
+        message: "Miner is not whitelisted".to_string(),
+    };
+    pub static ref INVALID_NONCE: Error = Error {
+        code: 7,
+        message: "Invalid nonce".to_string(),
+    };
+    pub static ref INVALID_NETWORK_ID: Error = Error {
+        code: 8,
+        message: "Invalid network id".to_string(),
     };
 }
@@ -0,0 +1,14 @@
+use ellipticoin::{Address, VersionedU128};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct BlockSealed {
+    pub block_number: u32,
+    pub miner: Address,
+}
+
+#[derive(Serialize)]
+pub struct Harvested {
+    pub address: Address,
+    pub amount: VersionedU128,
+}
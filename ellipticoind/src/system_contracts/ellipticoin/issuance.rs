@@ -8,22 +8,22 @@ pub const INCENTIVIZE_ELC_POOL_AT_BLOCK: u32 = 793_620;
 const BLOCKS_PER_ERA: u32 = 8_000_000;
 const NUMBER_OF_ERAS: u32 = 8;
 
-const V1_ISSUANCE: u64 = 130_036_019_000;
-const FIRST_ERA_ISSUANCE_PER_BLOCK: u64 = BASE_FACTOR * 128 / 100;
-const LAST_BLOCK_OF_FIRST_ERA: u32 = (((BLOCKS_PER_ERA as u64 * FIRST_ERA_ISSUANCE_PER_BLOCK)
+const V1_ISSUANCE: u128 = 130_036_019_000;
+const FIRST_ERA_ISSUANCE_PER_BLOCK: u128 = BASE_FACTOR * 128 / 100;
+const LAST_BLOCK_OF_FIRST_ERA: u32 = (((BLOCKS_PER_ERA as u128 * FIRST_ERA_ISSUANCE_PER_BLOCK)
     - V1_ISSUANCE)
     / FIRST_ERA_ISSUANCE_PER_BLOCK) as u32;
 
-pub fn block_reward_at(block: u32) -> u64 {
+pub fn block_reward_at(block: u32) -> u128 {
     if block > BLOCKS_PER_ERA * NUMBER_OF_ERAS {
         return 0;
     }
     if block <= LAST_BLOCK_OF_FIRST_ERA {
-        return FIRST_ERA_ISSUANCE_PER_BLOCK as u64;
+        return FIRST_ERA_ISSUANCE_PER_BLOCK;
     }
 
     let era = ((block - LAST_BLOCK_OF_FIRST_ERA) / BLOCKS_PER_ERA) + 1;
-    BASE_FACTOR * 128 * 10u64.pow(6) / 2u64.pow(era) / 10u64.pow(8)
+    BASE_FACTOR * 128 * 10u128.pow(6) / 2u128.pow(era) / 10u128.pow(8)
 }
 
 pub fn incentivized_pools_at(block: u32) -> Vec<Token> {
@@ -48,7 +48,7 @@ mod tests {
         let mut total_time: Duration = Default::default();
         for era in 0..=NUMBER_OF_ERAS - 1 {
             let reward = block_reward_at(era * BLOCKS_PER_ERA);
-            total_issuance += reward * BLOCKS_PER_ERA as u64;
+            total_issuance += reward * BLOCKS_PER_ERA as u128;
             total_time += BLOCKS_PER_ERA * BLOCK_TIME.clone();
         }
         assert_eq!(
@@ -61,11 +61,11 @@ mod tests {
 
     #[test]
     fn test_halvenings() {
-        let mut last_block_reward: u64 = block_reward_at(LAST_BLOCK_OF_FIRST_ERA);
+        let mut last_block_reward: u128 = block_reward_at(LAST_BLOCK_OF_FIRST_ERA);
         let mut block: u32 = LAST_BLOCK_OF_FIRST_ERA + 1;
 
         for _era in 1..=NUMBER_OF_ERAS - 1 {
-            let new_reward: u64 = block_reward_at(block);
+            let new_reward: u128 = block_reward_at(block);
             assert_eq!(new_reward, last_block_reward / 2);
             block += BLOCKS_PER_ERA;
             last_block_reward = new_reward;
@@ -1,4 +1,5 @@
 mod errors;
+mod events;
 mod hashing;
 mod issuance;
 
@@ -8,7 +9,10 @@ use crate::system_contracts::{
     exchange::liquidity_token,
     token::{constants::ELC, mint},
 };
-use ellipticoin::{pay, state_accessors, Address};
+use ellipticoin::{
+    helpers::{checked_add, checked_sub, mul_div},
+    pay, state_accessors, Address, VersionedU128,
+};
 
 use errors::Error;
 use hashing::sha256;
@@ -19,7 +23,7 @@ use serde_cbor::Value;
 use std::{collections::HashSet, convert::TryInto};
 use wasm_rpc_macros::export_native;
 
-const CONTRACT_NAME: &'static str = "Ellipticoin";
+pub const CONTRACT_NAME: &'static str = "Ellipticoin";
 
 lazy_static! {
     pub static ref ADDRESS: std::string::String = CONTRACT_NAME.to_string();
@@ -27,9 +31,10 @@ lazy_static! {
 
 state_accessors!(
     block_number() -> u32;
-    issuance_rewards(address: Address) -> u64;
+    issuance_rewards(address: Address) -> u128 as VersionedU128;
     miner_whitelist() -> HashSet<[u8; 32]>;
     miners() -> Vec<Miner>;
+    nonce(address: Address) -> u32;
 );
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -47,16 +52,21 @@ pub struct State {
 }
 
 export_native! {
-    pub fn harvest<API: ellipticoin::API>(api: &mut API) {
+    pub fn harvest<API: ellipticoin::API>(api: &mut API) -> Result<(), Box<Error>> {
         let issuance_rewards = get_issuance_rewards(api, api.caller());
-        debit_issuance_rewards(api, api.caller(), issuance_rewards);
-        pay!(api, ELC.clone(), api.caller(), issuance_rewards).unwrap();
+        debit_issuance_rewards(api, api.caller(), issuance_rewards)?;
+        pay!(api, ELC.clone(), api.caller(), issuance_rewards)?;
+        api.emit(CONTRACT_NAME, "Harvested", vec![api.caller()], events::Harvested {
+            address: api.caller(),
+            amount: issuance_rewards.into(),
+        });
+        Ok(())
     }
 
-    pub fn transfer_to_current_miner<API: ellipticoin::API>(api: &mut API, amount: u64) -> Result<(), Box<Error>> {
+    pub fn transfer_to_current_miner<API: ellipticoin::API>(api: &mut API, amount: u128) -> Result<(), Box<Error>> {
         let miners = get_miners(api);
         let current_miner = miners.first().unwrap().address.clone();
-        token::transfer_from(api, ELC.clone(), api.caller(), ellipticoin::Address::PublicKey(current_miner), amount)?;
+        token::transfer(api, ELC.clone(), api.caller(), ellipticoin::Address::PublicKey(current_miner), amount)?;
     Ok(())
     }
 
@@ -123,12 +133,57 @@ export_native! {
         shuffle_miners(api, &mut miners, value);
         issue_block_rewards(api)?;
         let block_number = increment_block_number(api);
+        api.emit(CONTRACT_NAME, "BlockSealed", vec![api.caller()], events::BlockSealed {
+            block_number,
+            miner: api.caller(),
+        });
 
         Ok(State{miners, block_number})
     }
 
 
 }
+
+// Returned instead of an `Error` so clients can resync their nonce without
+// parsing the message. It still decodes as an `Error`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct InvalidNonce {
+    pub code: usize,
+    pub message: String,
+    pub expected_nonce: u32,
+}
+
+pub fn validate_network_id(network_id: u32) -> Result<(), Box<Error>> {
+    if network_id != crate::config::network_id() {
+        return Err(Box::new(errors::INVALID_NETWORK_ID.clone()));
+    }
+    Ok(())
+}
+
+pub fn validate_nonce<API: ellipticoin::API>(
+    api: &mut API,
+    nonce: u32,
+) -> Result<(), InvalidNonce> {
+    let expected_nonce = get_nonce(api, api.caller()) + 1;
+    if nonce != expected_nonce {
+        return Err(InvalidNonce {
+            code: errors::INVALID_NONCE.code,
+            message: format!(
+                "{}: expected {}",
+                errors::INVALID_NONCE.message,
+                expected_nonce
+            ),
+            expected_nonce,
+        });
+    }
+    Ok(())
+}
+
+pub fn increment_nonce<API: ellipticoin::API>(api: &mut API) {
+    let nonce = get_nonce(api, api.caller());
+    set_nonce(api, api.caller(), nonce + 1);
+}
+
 fn issue_block_rewards<API: ellipticoin::API>(api: &mut API) -> Result<(), Box<Error>> {
     let block_number = get_block_number(api);
     let block_reward = block_reward_at(block_number);
@@ -139,7 +194,7 @@ fn issue_block_rewards<API: ellipticoin::API>(api: &mut API) -> Result<(), Box<E
         block_reward,
     )?;
     let incentivized_pools = incentivized_pools_at(block_number);
-    let reward_per_pool = block_reward / incentivized_pools.len() as u64;
+    let reward_per_pool = block_reward / incentivized_pools.len() as u128;
     for token in incentivized_pools.clone() {
         let share_holders = exchange::get_share_holders(api, token.clone());
         let (addresses, balances): (Vec<_>, Vec<_>) = share_holders
@@ -151,16 +206,16 @@ fn issue_block_rewards<API: ellipticoin::API>(api: &mut API) -> Result<(), Box<E
                     token::get_balance(api, liquidity_token(token.clone()), address),
                 )
             })
-            .collect::<Vec<(Address, u64)>>()
+            .collect::<Vec<(Address, u128)>>()
             .iter()
             .cloned()
             .unzip();
 
         for (address, issuance) in addresses
             .iter()
-            .zip(distribute(reward_per_pool, balances).iter())
+            .zip(distribute(reward_per_pool, balances)?.iter())
         {
-            credit_issuance_rewards(api, address.clone(), *issuance);
+            credit_issuance_rewards(api, address.clone(), *issuance)?;
         }
     }
     Ok(())
@@ -187,6 +242,8 @@ fn shuffle_miners<API: ellipticoin::API>(api: &mut API, miners: &mut Vec<Miner>,
     set_miners(api, shuffled_miners);
 }
 
+// Burns skip the pause and freeze checks. ELC is issued by this contract which
+// doesn't export `pause` or `freeze`, so they can't apply to it.
 fn settle_block_rewards<API: ellipticoin::API>(api: &mut API) -> Result<(), Box<Error>> {
     let miners = get_miners(api);
     let winner = miners.first().as_ref().unwrap().clone();
@@ -194,57 +251,71 @@ fn settle_block_rewards<API: ellipticoin::API>(api: &mut API) -> Result<(), Box<
         credit(
             api,
             ellipticoin::Address::PublicKey(winner.address.clone()),
-            miner.burn_per_block,
-        );
+            miner.burn_per_block.into(),
+        )?;
         debit(
             api,
             ellipticoin::Address::PublicKey(miner.address.clone()),
-            miner.burn_per_block,
+            miner.burn_per_block.into(),
         )?;
     }
     Ok(())
 }
 
-fn credit<API: ellipticoin::API>(api: &mut API, address: Address, amount: u64) {
-    token::credit(api, ELC.clone(), address, amount);
+fn credit<API: ellipticoin::API>(
+    api: &mut API,
+    address: Address,
+    amount: u128,
+) -> Result<(), Box<Error>> {
+    token::credit(api, ELC.clone(), address, amount)
 }
 
 fn debit<API: ellipticoin::API>(
     api: &mut API,
     address: Address,
-    amount: u64,
+    amount: u128,
 ) -> Result<(), Box<Error>> {
     token::debit(api, ELC.clone(), address, amount)?;
     Ok(())
 }
 
-fn credit_issuance_rewards<API: ellipticoin::API>(api: &mut API, address: Address, amount: u64) {
+fn credit_issuance_rewards<API: ellipticoin::API>(
+    api: &mut API,
+    address: Address,
+    amount: u128,
+) -> Result<(), Box<Error>> {
     let issuance_rewards = get_issuance_rewards(api, address.clone());
-    set_issuance_rewards(api, address, issuance_rewards + amount);
+    set_issuance_rewards(api, address, checked_add(issuance_rewards, amount)?);
+    Ok(())
 }
 
-fn debit_issuance_rewards<API: ellipticoin::API>(api: &mut API, address: Address, amount: u64) {
+fn debit_issuance_rewards<API: ellipticoin::API>(
+    api: &mut API,
+    address: Address,
+    amount: u128,
+) -> Result<(), Box<Error>> {
     let issuance_rewards = get_issuance_rewards(api, address.clone());
-    set_issuance_rewards(api, address, issuance_rewards - amount);
+    set_issuance_rewards(api, address, checked_sub(issuance_rewards, amount)?);
+    Ok(())
 }
 
-fn distribute(mut amount: u64, mut values: Vec<u64>) -> Vec<u64> {
+fn distribute(mut amount: u128, mut values: Vec<u128>) -> Result<Vec<u128>, Box<Error>> {
     let mut rest = values.clone();
-    let mut distributions: Vec<u64> = Default::default();
+    let mut distributions: Vec<u128> = Default::default();
     values.reverse();
     for balance in values.clone() {
-        let denominator = rest.iter().sum::<u64>();
+        let denominator = rest.iter().sum::<u128>();
         let distribution = if denominator == 0 {
             0
         } else {
-            (amount * balance) / denominator
+            mul_div(amount, balance, denominator)?
         };
         amount -= distribution;
         distributions.push(distribution);
         rest.pop();
     }
     distributions.reverse();
-    distributions
+    Ok(distributions)
 }
 
 #[cfg(test)]
@@ -252,6 +323,7 @@ mod tests {
     use super::*;
     use crate::{
         config::HOST,
+        constants::ACTIVATION_HEIGHT,
         helpers::generate_hash_onion,
         system_contracts::{
             ellipticoin::issuance::INCENTIVIZE_ELC_POOL_AT_BLOCK,
@@ -262,6 +334,7 @@ mod tests {
                 get_balance, BASE_FACTOR,
             },
         },
+        transaction::TransactionRequest,
     };
     use ellipticoin_test_framework::{
         constants::actors::{ALICE, ALICES_PRIVATE_KEY, BOB, CAROL},
@@ -275,14 +348,39 @@ mod tests {
         let mut state = HashMap::new();
         let mut api = TestAPI::new(&mut state, *ALICE, "Ellipticoin".to_string());
         mint(&mut api, ELC.clone(), Address::Contract(ADDRESS.clone()), 1).unwrap();
-        credit_issuance_rewards(&mut api, Address::PublicKey(*ALICE), 1);
-        native::harvest(&mut api);
+        credit_issuance_rewards(&mut api, Address::PublicKey(*ALICE), 1).unwrap();
+        native::harvest(&mut api).unwrap();
         assert_eq!(
             get_balance(&mut api, ELC.clone(), Address::PublicKey(*ALICE)),
             1
         );
     }
 
+    #[test]
+    fn test_credit_issuance_rewards_overflow() {
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Ellipticoin".to_string());
+        credit_issuance_rewards(&mut api, Address::PublicKey(*ALICE), u128::MAX).unwrap();
+        assert_eq!(
+            credit_issuance_rewards(&mut api, Address::PublicKey(*ALICE), 1)
+                .unwrap_err()
+                .code,
+            ellipticoin::errors::ARITHMETIC_OVERFLOW.code
+        );
+        assert_eq!(
+            get_issuance_rewards(&mut api, Address::PublicKey(*ALICE)),
+            u128::MAX
+        );
+    }
+
+    #[test]
+    fn test_distribute_large_balances() {
+        assert_eq!(
+            distribute(10, vec![u64::MAX as u128, u64::MAX as u128]).unwrap(),
+            vec![5, 5]
+        );
+    }
+
     #[test]
     fn test_issue_block_rewards_btc() {
         let mut state = HashMap::new();
@@ -425,8 +523,8 @@ mod tests {
         env::set_var("HOST", "localhost");
         let mut state = HashMap::new();
         let mut api = TestAPI::new(&mut state, *ALICE, "Ellipticoin".to_string());
-        credit(&mut api, Address::PublicKey(*ALICE), 5);
-        credit(&mut api, Address::PublicKey(*BOB), 5);
+        credit(&mut api, Address::PublicKey(*ALICE), 5).unwrap();
+        credit(&mut api, Address::PublicKey(*BOB), 5).unwrap();
         let alices_center = [0; 32];
         let bobs_center = [1; 32];
         let mut alices_onion = generate_hash_onion(3, alices_center.clone());
@@ -496,4 +594,94 @@ mod tests {
         );
         assert_eq!(get_block_number(&mut api), 3);
     }
+
+    #[test]
+    fn test_elc_cannot_be_paused_or_frozen() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        assert_eq!(
+            token::native::pause(&mut api, ELC.clone())
+                .unwrap_err()
+                .code,
+            token::errors::NOT_TOKEN_ISSUER.code
+        );
+        assert_eq!(
+            token::native::freeze(&mut api, ELC.clone(), BOB.to_vec().into())
+                .unwrap_err()
+                .code,
+            token::errors::NOT_TOKEN_ISSUER.code
+        );
+    }
+
+    fn run_transaction(api: &mut TestAPI, network_id: u32, nonce: u32) -> Value {
+        crate::system_contracts::run(
+            api,
+            TransactionRequest {
+                network_id,
+                contract: CONTRACT_NAME.to_string(),
+                sender: *ALICE,
+                nonce,
+                function: "harvest".to_string(),
+                arguments: vec![],
+            },
+        )
+    }
+
+    fn run_harvest(api: &mut TestAPI, network_id: u32, nonce: u32) -> Result<Value, Error> {
+        serde_cbor::value::from_value(run_transaction(api, network_id, nonce)).unwrap()
+    }
+
+    #[test]
+    fn test_nonces() {
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Ellipticoin".to_string());
+        assert!(run_harvest(&mut api, 0, 1).is_ok());
+        let error = run_harvest(&mut api, 0, 1).unwrap_err();
+        assert_eq!(error.code, errors::INVALID_NONCE.code);
+        assert_eq!(error.message, "Invalid nonce: expected 2");
+        let error: Result<Value, InvalidNonce> =
+            serde_cbor::value::from_value(run_transaction(&mut api, 0, 1)).unwrap();
+        assert_eq!(error.unwrap_err().expected_nonce, 2);
+        assert!(run_harvest(&mut api, 0, 3).is_err());
+        assert!(run_harvest(&mut api, 0, 2).is_ok());
+        assert_eq!(get_nonce(&mut api, Address::PublicKey(*ALICE)), 2);
+    }
+
+    #[test]
+    fn test_nonces_below_activation_height() {
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Ellipticoin".to_string());
+        let transaction = TransactionRequest {
+            network_id: 0,
+            contract: CONTRACT_NAME.to_string(),
+            sender: *ALICE,
+            nonce: 2,
+            function: "harvest".to_string(),
+            arguments: vec![],
+        };
+        let return_value: Result<Value, Error> =
+            serde_cbor::value::from_value(crate::system_contracts::run_in_block(
+                &mut api,
+                transaction.clone(),
+                *ACTIVATION_HEIGHT - 1,
+            ))
+            .unwrap();
+        assert!(return_value.is_ok());
+        assert_eq!(get_nonce(&mut api, Address::PublicKey(*ALICE)), 2);
+        let return_value: Result<Value, Error> = serde_cbor::value::from_value(
+            crate::system_contracts::run_in_block(&mut api, transaction, *ACTIVATION_HEIGHT),
+        )
+        .unwrap();
+        assert_eq!(return_value.unwrap_err().code, errors::INVALID_NONCE.code);
+    }
+
+    #[test]
+    fn test_foreign_network_id() {
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Ellipticoin".to_string());
+        let error = run_harvest(&mut api, 1, 1).unwrap_err();
+        assert_eq!(error.code, errors::INVALID_NETWORK_ID.code);
+        assert_eq!(get_nonce(&mut api, Address::PublicKey(*ALICE)), 0);
+    }
 }
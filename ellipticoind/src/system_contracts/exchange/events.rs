@@ -0,0 +1,27 @@
+use ellipticoin::{Address, Token, VersionedU128};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct LiquidityAdded {
+    pub token: Token,
+    pub provider: Address,
+    pub amount: VersionedU128,
+    pub base_token_amount: VersionedU128,
+}
+
+#[derive(Serialize)]
+pub struct LiquidityRemoved {
+    pub token: Token,
+    pub provider: Address,
+    pub amount: VersionedU128,
+    pub base_token_amount: VersionedU128,
+}
+
+#[derive(Serialize)]
+pub struct Swap {
+    pub trader: Address,
+    pub input_token: Token,
+    pub output_token: Token,
+    pub input_amount: VersionedU128,
+    pub output_amount: VersionedU128,
+}
@@ -1,12 +1,17 @@
 pub mod constants;
 mod errors;
+mod events;
 
 use crate::{
     helpers::sha256,
     system_contracts::token::{self, BASE_FACTOR},
 };
 use constants::{BASE_TOKEN, FEE};
-use ellipticoin::{charge, pay, state_accessors, Address, Token};
+use ellipticoin::{
+    charge,
+    helpers::{checked_add, mul_div},
+    pay, state_accessors, Address, Token, VersionedU128,
+};
 use std::{boxed::Box, collections::HashSet, str};
 use wasm_rpc::error::Error;
 use wasm_rpc_macros::export_native;
@@ -17,12 +22,16 @@ lazy_static! {
 }
 
 state_accessors!(
-    pool_supply_of_base_token(token: Token) -> u64;
-    pool_supply_of_token(token: Token) -> u64;
+    pool_supply_of_base_token(token: Token) -> u128 as VersionedU128;
+    pool_supply_of_token(token: Token) -> u128 as VersionedU128;
     share_holders(token: Token) -> HashSet<Address>;
 );
 
-fn mint<API: ellipticoin::API>(api: &mut API, token: Token, amount: u64) -> Result<(), Box<Error>> {
+fn mint<API: ellipticoin::API>(
+    api: &mut API,
+    token: Token,
+    amount: u128,
+) -> Result<(), Box<Error>> {
     token::mint(api, liquidity_token(token.clone()), api.caller(), amount)?;
     let mut share_holders = get_share_holders(api, token.clone());
     share_holders.insert(api.caller());
@@ -34,58 +43,79 @@ export_native! {
     pub fn create_pool<API: ellipticoin::API>(
         api: &mut API,
         token: Token,
-        amount: u64,
-        starting_price: u64,
+        amount: u128,
+        starting_price: u128,
     ) -> Result<(), Box<Error>> {
         validate_pool_does_not_exist(api, token.clone())?;
         charge!(api, token.clone(), api.caller(), amount)?;
-        credit_pool_supply_of_token(api, token.clone(), amount);
-        charge!(api, BASE_TOKEN.clone(), api.caller(), ((amount as u128 * starting_price as u128) / BASE_FACTOR as u128) as u64)?;
-        credit_pool_supply_of_base_token(api, token.clone(), (amount * starting_price) / BASE_FACTOR);
-        mint(api, token, amount)?;
+        credit_pool_supply_of_token(api, token.clone(), amount)?;
+        let base_token_amount = mul_div(amount, starting_price, BASE_FACTOR)?;
+        charge!(api, BASE_TOKEN.clone(), api.caller(), base_token_amount)?;
+        credit_pool_supply_of_base_token(api, token.clone(), base_token_amount)?;
+        mint(api, token.clone(), amount)?;
+        api.emit(CONTRACT_NAME, "LiquidityAdded", vec![api.caller()], events::LiquidityAdded {
+            token,
+            provider: api.caller(),
+            amount: amount.into(),
+            base_token_amount: base_token_amount.into(),
+        });
         Ok(())
     }
 
     pub fn add_liquidity<API: ellipticoin::API>(
         api: &mut API,
         token: Token,
-        amount: u64,
+        amount: u128,
     ) -> Result<(), Box<Error>> {
         validate_pool_exists(api, token.clone())?;
         let pool_supply_of_token = get_pool_supply_of_token(api, token.clone());
         let pool_supply_of_base_token = get_pool_supply_of_base_token(api, token.clone());
         let total_supply_of_liquidity_token = token::get_total_supply(api, liquidity_token(token.clone()));
 
-        let mint_amount = (amount as u128 * total_supply_of_liquidity_token as u128 / pool_supply_of_token as u128) as u64;
+        let mint_amount = mul_div(amount, total_supply_of_liquidity_token, pool_supply_of_token)?;
 
         charge!(api, token.clone(), api.caller(), amount)?;
-        credit_pool_supply_of_token(api, token.clone(), amount);
-
-        charge!(api, BASE_TOKEN.clone(), api.caller(), ((amount as u128 * pool_supply_of_base_token as u128)/pool_supply_of_token as u128) as u64)?;
-        credit_pool_supply_of_base_token(api, token.clone(), (amount * pool_supply_of_base_token)/pool_supply_of_token);
-
-        mint(api, token, mint_amount)?;
+        credit_pool_supply_of_token(api, token.clone(), amount)?;
+
+        let base_token_amount = mul_div(amount, pool_supply_of_base_token, pool_supply_of_token)?;
+        charge!(api, BASE_TOKEN.clone(), api.caller(), base_token_amount)?;
+        credit_pool_supply_of_base_token(api, token.clone(), base_token_amount)?;
+
+        mint(api, token.clone(), mint_amount)?;
+        api.emit(CONTRACT_NAME, "LiquidityAdded", vec![api.caller()], events::LiquidityAdded {
+            token,
+            provider: api.caller(),
+            amount: amount.into(),
+            base_token_amount: base_token_amount.into(),
+        });
         Ok(())
     }
 
     pub fn remove_liquidity<API: ellipticoin::API>(
         api: &mut API,
         token: Token,
-        percentage: u64,
+        percentage: u128,
     ) -> Result<(), Box<Error>> {
         let pool_supply_of_token = get_pool_supply_of_token(api, token.clone());
         let pool_supply_of_base_token = get_pool_supply_of_base_token(api, token.clone());
         let liquidity_token_balance = token::get_balance(api, liquidity_token(token.clone()), api.caller());
         let total_supply_of_liquidity_token = token::get_total_supply(api, liquidity_token(token.clone()));
-        let token_balance = pool_supply_of_token as u128 * liquidity_token_balance as u128 / total_supply_of_liquidity_token as u128;
-        let base_token_balance = pool_supply_of_base_token as u128 * liquidity_token_balance as u128 / total_supply_of_liquidity_token as u128;
-
-        burn_liquidity(api, token.clone(), (liquidity_token_balance as u128 * percentage as u128 / BASE_FACTOR as u128) as u64)?;
-        debit_pool_supply_of_base_token(api, token.clone(), (base_token_balance as u128 * percentage as u128 / BASE_FACTOR as u128) as u64)?;
-        pay!(api, BASE_TOKEN.clone(), api.caller(), (base_token_balance as u128 * percentage as u128 / BASE_FACTOR as u128) as u64)?;
-        debit_pool_supply_of_token(api, token.clone(), (percentage as u128 * token_balance as u128/ BASE_FACTOR as u128) as u64)?;
-        pay!(api, token, api.caller(), (percentage as u128 * token_balance as u128 / BASE_FACTOR as u128) as u64)?;
-
+        let token_balance = mul_div(pool_supply_of_token, liquidity_token_balance, total_supply_of_liquidity_token)?;
+        let base_token_balance = mul_div(pool_supply_of_base_token, liquidity_token_balance, total_supply_of_liquidity_token)?;
+
+        let base_token_amount = mul_div(base_token_balance, percentage, BASE_FACTOR)?;
+        let amount = mul_div(percentage, token_balance, BASE_FACTOR)?;
+        burn_liquidity(api, token.clone(), mul_div(liquidity_token_balance, percentage, BASE_FACTOR)?)?;
+        debit_pool_supply_of_base_token(api, token.clone(), base_token_amount)?;
+        pay!(api, BASE_TOKEN.clone(), api.caller(), base_token_amount)?;
+        debit_pool_supply_of_token(api, token.clone(), amount)?;
+        pay!(api, token.clone(), api.caller(), amount)?;
+        api.emit(CONTRACT_NAME, "LiquidityRemoved", vec![api.caller()], events::LiquidityRemoved {
+            token,
+            provider: api.caller(),
+            amount: amount.into(),
+            base_token_amount: base_token_amount.into(),
+        });
         Ok(())
     }
 
@@ -93,16 +123,23 @@ export_native! {
         api: &mut API,
         input_token: Token,
         output_token: Token,
-        input_amount: u64,
-        minimum_output_token_amount: u64
+        input_amount: u128,
+        minimum_output_token_amount: u128
     ) -> Result<(), Box<Error>> {
         charge!(api, input_token.clone(), api.caller(), input_amount)?;
-        let base_token_amount = exchange_token_for_base_token(api, input_token, input_amount)?;
+        let base_token_amount = exchange_token_for_base_token(api, input_token.clone(), input_amount)?;
         let output_token_amount = exchange_base_token_for_token(api, output_token.clone(), base_token_amount)?;
         if output_token_amount < minimum_output_token_amount {
             return Err(Box::new(errors::MAX_SLIPPAGE_EXCEEDED.clone()))
         }
-        pay!(api, output_token, api.caller(), output_token_amount)?;
+        pay!(api, output_token.clone(), api.caller(), output_token_amount)?;
+        api.emit(CONTRACT_NAME, "Swap", vec![api.caller()], events::Swap {
+            trader: api.caller(),
+            input_token,
+            output_token,
+            input_amount: input_amount.into(),
+            output_amount: output_token_amount.into(),
+        });
         Ok(())
     }
 }
@@ -110,19 +147,19 @@ export_native! {
 fn exchange_token_for_base_token<API: ellipticoin::API>(
     api: &mut API,
     token: Token,
-    amount: u64,
-) -> Result<u64, Box<Error>> {
+    amount: u128,
+) -> Result<u128, Box<Error>> {
     if token == BASE_TOKEN.clone() {
         return Ok(amount);
     };
     validate_pool_exists(api, token.clone())?;
-    let amount_minus_fee = apply_fee(amount);
+    let amount_minus_fee = apply_fee(amount)?;
     let base_token_output_amount = calculate_output_amount(
         get_pool_supply_of_token(api, token.clone()),
         get_pool_supply_of_base_token(api, token.clone()),
         amount_minus_fee,
-    );
-    credit_pool_supply_of_token(api, token.clone(), amount);
+    )?;
+    credit_pool_supply_of_token(api, token.clone(), amount)?;
     debit_pool_supply_of_base_token(api, token.clone(), base_token_output_amount)?;
     Ok(base_token_output_amount)
 }
@@ -130,32 +167,38 @@ fn exchange_token_for_base_token<API: ellipticoin::API>(
 fn exchange_base_token_for_token<API: ellipticoin::API>(
     api: &mut API,
     token: Token,
-    amount: u64,
-) -> Result<u64, Box<Error>> {
+    amount: u128,
+) -> Result<u128, Box<Error>> {
     if token == BASE_TOKEN.clone() {
         return Ok(amount);
     };
     validate_pool_exists(api, token.clone())?;
-    let amount_minus_fee = apply_fee(amount);
+    let amount_minus_fee = apply_fee(amount)?;
     let output_amount = calculate_output_amount(
         get_pool_supply_of_base_token(api, token.clone()),
         get_pool_supply_of_token(api, token.clone()),
         amount_minus_fee,
-    );
+    )?;
     debit_pool_supply_of_token(api, token.clone(), output_amount)?;
-    credit_pool_supply_of_base_token(api, token.clone(), amount);
+    credit_pool_supply_of_base_token(api, token.clone(), amount)?;
     Ok(output_amount)
 }
 
-fn calculate_output_amount(input_supply: u64, output_supply: u64, input_amount: u64) -> u64 {
-    let invariant = input_supply as u128 * output_supply as u128;
-    let new_output_supply =
-        (invariant / (input_supply as u128 + input_amount as u128) as u128) as u64;
-    output_supply - new_output_supply
+fn calculate_output_amount(
+    input_supply: u128,
+    output_supply: u128,
+    input_amount: u128,
+) -> Result<u128, Box<Error>> {
+    let new_output_supply = mul_div(
+        input_supply,
+        output_supply,
+        checked_add(input_supply, input_amount)?,
+    )?;
+    Ok(output_supply - new_output_supply)
 }
 
-fn apply_fee(amount: u64) -> u64 {
-    amount - ((amount as u128 * FEE as u128) / BASE_FACTOR as u128) as u64
+fn apply_fee(amount: u128) -> Result<u128, Box<Error>> {
+    Ok(amount - mul_div(amount, FEE, BASE_FACTOR)?)
 }
 
 fn validate_pool_does_not_exist<API: ellipticoin::API>(
@@ -180,29 +223,138 @@ fn validate_pool_exists<API: ellipticoin::API>(
     }
 }
 
-pub fn get_price<API: ellipticoin::API>(api: &mut API, token: Token) -> Result<u64, Box<Error>> {
+pub fn get_price<API: ellipticoin::API>(api: &mut API, token: Token) -> Result<u128, Box<Error>> {
     let pool_supply_of_base_token = get_pool_supply_of_base_token(api, token.clone());
     let pool_supply_of_token = get_pool_supply_of_token(api, token.clone());
     if pool_supply_of_token == 0 {
         Err(Box::new(errors::POOL_NOT_FOUND.clone()))
     } else {
-        Ok(pool_supply_of_base_token * BASE_FACTOR / pool_supply_of_token)
+        mul_div(pool_supply_of_base_token, BASE_FACTOR, pool_supply_of_token)
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quote {
+    pub input_amount: u128,
+    pub base_token_amount: u128,
+    pub output_amount: u128,
+    pub input_token_fee: u128,
+    pub base_token_fee: u128,
+    pub price_impact: u128,
+    pub spot_price: u128,
+}
+
+// Quotes an exchange by running it against the pools and rolling the changes
+// back. Balances and slippage aren't checked.
+pub fn quote<API: ellipticoin::API>(
+    api: &mut API,
+    input_token: Token,
+    output_token: Token,
+    input_amount: u128,
+) -> Result<Quote, Box<Error>> {
+    let pool_supply_of_input_token = get_pool_supply_of_token(api, input_token.clone());
+    let pool_supply_of_input_base_token = get_pool_supply_of_base_token(api, input_token.clone());
+    let pool_supply_of_output_token = get_pool_supply_of_token(api, output_token.clone());
+    let pool_supply_of_output_base_token = get_pool_supply_of_base_token(api, output_token.clone());
+    api.checkpoint();
+    let result = simulate_exchange(api, input_token.clone(), output_token.clone(), input_amount);
+    api.rollback();
+    let (base_token_amount, output_amount, spot_price) = result?;
+
+    let (input_token_fee, spot_base_token_amount) = if input_token == BASE_TOKEN.clone() {
+        (0, input_amount)
+    } else {
+        (
+            input_amount - apply_fee(input_amount)?,
+            spot_output_amount(
+                pool_supply_of_input_token,
+                pool_supply_of_input_base_token,
+                apply_fee(input_amount)?,
+            )?,
+        )
+    };
+    let (base_token_fee, spot_amount) = if output_token == BASE_TOKEN.clone() {
+        (0, spot_base_token_amount)
+    } else {
+        (
+            base_token_amount - apply_fee(base_token_amount)?,
+            spot_output_amount(
+                pool_supply_of_output_base_token,
+                pool_supply_of_output_token,
+                apply_fee(spot_base_token_amount)?,
+            )?,
+        )
+    };
+    let price_impact = if spot_amount == 0 {
+        0
+    } else {
+        mul_div(
+            spot_amount.saturating_sub(output_amount),
+            BASE_FACTOR,
+            spot_amount,
+        )?
+    };
+
+    Ok(Quote {
+        input_amount,
+        base_token_amount,
+        output_amount,
+        input_token_fee,
+        base_token_fee,
+        price_impact,
+        spot_price,
+    })
+}
+
+fn simulate_exchange<API: ellipticoin::API>(
+    api: &mut API,
+    input_token: Token,
+    output_token: Token,
+    input_amount: u128,
+) -> Result<(u128, u128, u128), Box<Error>> {
+    let base_token_amount = exchange_token_for_base_token(api, input_token.clone(), input_amount)?;
+    let output_amount =
+        exchange_base_token_for_token(api, output_token.clone(), base_token_amount)?;
+    let spot_price = mul_div(
+        price_in_base_token(api, input_token)?,
+        BASE_FACTOR,
+        price_in_base_token(api, output_token)?,
+    )?;
+    Ok((base_token_amount, output_amount, spot_price))
+}
+
+fn price_in_base_token<API: ellipticoin::API>(
+    api: &mut API,
+    token: Token,
+) -> Result<u128, Box<Error>> {
+    if token == BASE_TOKEN.clone() {
+        return Ok(BASE_FACTOR);
+    }
+    get_price(api, token)
+}
+
+fn spot_output_amount(
+    input_supply: u128,
+    output_supply: u128,
+    input_amount: u128,
+) -> Result<u128, Box<Error>> {
+    mul_div(input_amount, output_supply, input_supply)
+}
+
 fn credit_pool_supply_of_base_token<API: ellipticoin::API>(
     api: &mut API,
     token: Token,
-    amount: u64,
-) {
+    amount: u128,
+) -> Result<(), Box<Error>> {
     let base_token_supply = get_pool_supply_of_base_token(api, token.clone());
-    set_pool_supply_of_base_token(api, token.clone(), base_token_supply + amount);
+    set_pool_supply_of_base_token(api, token.clone(), checked_add(base_token_supply, amount)?);
+    Ok(())
 }
 
 pub fn debit_pool_supply_of_base_token<API: ellipticoin::API>(
     api: &mut API,
     token: Token,
-    amount: u64,
+    amount: u128,
 ) -> Result<(), Box<Error>> {
     let base_token_supply = get_pool_supply_of_base_token(api, token.clone());
     if amount <= base_token_supply {
@@ -216,15 +368,20 @@ pub fn debit_pool_supply_of_base_token<API: ellipticoin::API>(
     }
 }
 
-fn credit_pool_supply_of_token<API: ellipticoin::API>(api: &mut API, token: Token, amount: u64) {
+fn credit_pool_supply_of_token<API: ellipticoin::API>(
+    api: &mut API,
+    token: Token,
+    amount: u128,
+) -> Result<(), Box<Error>> {
     let token_supply = get_pool_supply_of_token(api, token.clone());
-    set_pool_supply_of_token(api, token.clone(), token_supply + amount);
+    set_pool_supply_of_token(api, token.clone(), checked_add(token_supply, amount)?);
+    Ok(())
 }
 
 pub fn debit_pool_supply_of_token<API: ellipticoin::API>(
     api: &mut API,
     token: Token,
-    amount: u64,
+    amount: u128,
 ) -> Result<(), Box<Error>> {
     let token_supply = get_pool_supply_of_token(api, token.clone());
     if amount <= token_supply {
@@ -241,7 +398,7 @@ pub fn debit_pool_supply_of_token<API: ellipticoin::API>(
 pub fn burn_liquidity<API: ellipticoin::API>(
     api: &mut API,
     token: Token,
-    amount: u64,
+    amount: u128,
 ) -> Result<(), Box<Error>> {
     token::burn(api, liquidity_token(token.clone()), api.caller(), amount)?;
     if token::get_balance(api, liquidity_token(token.clone()), api.caller()) == 0 {
@@ -252,6 +409,21 @@ pub fn burn_liquidity<API: ellipticoin::API>(
     Ok(())
 }
 
+// Liquidity tokens are issued by this contract so they get their metadata from
+// the token in the pool instead
+pub fn get_liquidity_token_metadata<API: ellipticoin::API>(
+    api: &mut API,
+    token: Token,
+) -> Option<token::Metadata> {
+    validate_pool_exists(api, token.clone()).ok()?;
+    let metadata = token::get_metadata(api, token)?;
+    Some(token::Metadata {
+        name: format!("{} Liquidity", metadata.name),
+        symbol: format!("{}-LP", metadata.symbol),
+        ..metadata
+    })
+}
+
 pub fn liquidity_token(token: Token) -> Token {
     Token {
         issuer: Address::Contract(CONTRACT_NAME.to_string()),
@@ -262,7 +434,10 @@ pub fn liquidity_token(token: Token) -> Token {
 #[cfg(test)]
 mod tests {
     use super::{native, *};
-    use crate::system_contracts::token::{self, constants::ELC};
+    use crate::{
+        system_contracts::token::{self, constants::ELC},
+        transaction::TransactionRequest,
+    };
     use ellipticoin::API;
     use ellipticoin_test_framework::{
         constants::{
@@ -307,6 +482,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_liquidity_token_metadata() {
+        let mut state = HashMap::new();
+        let mut api = setup(
+            hashmap! {
+                ellipticoin::Address::PublicKey(*ALICE) => vec![
+                    (APPLES.clone(), 1 * BASE_FACTOR),
+                    (BASE_TOKEN.clone(), 1 * BASE_FACTOR),
+                ]
+            },
+            &mut state,
+        );
+        token::update_metadata(
+            &mut api,
+            APPLES.clone(),
+            token::Metadata {
+                name: "Apples".to_string(),
+                symbol: "APL".to_string(),
+                decimals: 6,
+                website: "https://apples.example".to_string(),
+            },
+        );
+        assert!(get_liquidity_token_metadata(&mut api, APPLES.clone()).is_none());
+        native::create_pool(&mut api, APPLES.clone(), 1 * BASE_FACTOR, 1 * BASE_FACTOR).unwrap();
+        assert_eq!(
+            get_liquidity_token_metadata(&mut api, APPLES.clone()),
+            Some(token::Metadata {
+                name: "Apples Liquidity".to_string(),
+                symbol: "APL-LP".to_string(),
+                decimals: 6,
+                website: "https://apples.example".to_string(),
+            })
+        );
+        assert!(get_liquidity_token_metadata(&mut api, BANANAS.clone()).is_none());
+    }
+
     #[test]
     fn test_recreate_pool() {
         let mut state = HashMap::new();
@@ -718,6 +929,68 @@ mod tests {
         assert!(native::remove_liquidity(&mut api, APPLES.clone(), 2 * BASE_FACTOR).is_err());
     }
 
+    #[test]
+    fn test_remove_liquidity_rolls_back_partial_payment() {
+        let mut state = HashMap::new();
+        let mut api = setup(
+            hashmap! {
+                ellipticoin::Address::PublicKey(*ALICE) =>
+                vec![
+                    (APPLES.clone(), 1 * BASE_FACTOR),
+                    (BASE_TOKEN.clone(), 1 * BASE_FACTOR),
+                ],
+            },
+            &mut state,
+        );
+
+        native::create_pool(&mut api, APPLES.clone(), 1 * BASE_FACTOR, BASE_FACTOR).unwrap();
+        token::set_balance(
+            &mut api,
+            APPLES.clone(),
+            Address::Contract(ADDRESS.clone()),
+            0,
+        );
+        api.commit();
+        let return_value: Result<serde_cbor::Value, Error> =
+            serde_cbor::value::from_value(crate::system_contracts::run(
+                &mut api,
+                TransactionRequest {
+                    network_id: 0,
+                    contract: CONTRACT_NAME.to_string(),
+                    sender: *ALICE,
+                    nonce: 1,
+                    function: "remove_liquidity".to_string(),
+                    arguments: vec![
+                        serde_cbor::value::to_value(APPLES.clone()).unwrap(),
+                        serde_cbor::value::to_value(BASE_FACTOR as u64).unwrap(),
+                    ],
+                },
+            ))
+            .unwrap();
+        assert!(return_value.is_err());
+
+        assert_eq!(
+            token::get_balance(&mut api, BASE_TOKEN.clone(), Address::PublicKey(*ALICE)),
+            0
+        );
+        assert_eq!(
+            token::get_balance(
+                &mut api,
+                liquidity_token(APPLES.clone()),
+                Address::PublicKey(*ALICE)
+            ),
+            1 * BASE_FACTOR
+        );
+        assert_eq!(
+            get_pool_supply_of_base_token(&mut api, APPLES.clone()),
+            1 * BASE_FACTOR
+        );
+        assert_eq!(
+            get_pool_supply_of_token(&mut api, APPLES.clone()),
+            1 * BASE_FACTOR
+        );
+    }
+
     #[test]
     fn test_exchange() {
         let mut state = HashMap::new();
@@ -757,6 +1030,135 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_create_pool_overflow() {
+        let mut state = HashMap::new();
+        let mut api = setup(
+            hashmap! {
+                ellipticoin::Address::PublicKey(*ALICE) =>
+                vec![
+                    (APPLES.clone(), u128::MAX),
+                    (BASE_TOKEN.clone(), u128::MAX),
+                ],
+            },
+            &mut state,
+        );
+
+        assert_eq!(
+            native::create_pool(&mut api, APPLES.clone(), u128::MAX, 2 * BASE_FACTOR)
+                .unwrap_err()
+                .code,
+            ellipticoin::errors::ARITHMETIC_OVERFLOW.code
+        );
+    }
+
+    #[test]
+    fn test_exchange_overflow() {
+        let mut state = HashMap::new();
+        let mut api = setup(
+            hashmap! {
+                ellipticoin::Address::PublicKey(*ALICE) =>
+                vec![
+                    (APPLES.clone(), u128::MAX),
+                    (BASE_TOKEN.clone(), u128::MAX),
+                ],
+                ellipticoin::Address::PublicKey(*BOB) =>
+                vec![(APPLES.clone(), 2)],
+            },
+            &mut state,
+        );
+
+        native::create_pool(&mut api, APPLES.clone(), u128::MAX - 1, 1).unwrap();
+        api.caller = Address::PublicKey(BOB.clone());
+        assert_eq!(
+            native::exchange(&mut api, APPLES.clone(), BASE_TOKEN.clone(), 2, 0)
+                .unwrap_err()
+                .code,
+            ellipticoin::errors::ARITHMETIC_OVERFLOW.code
+        );
+    }
+
+    #[test]
+    fn test_quote() {
+        let mut state = HashMap::new();
+        let mut api = setup(
+            hashmap! {
+                    ellipticoin::Address::PublicKey(*ALICE) =>
+                    vec![
+                        (APPLES.clone(), 100 * BASE_FACTOR),
+                        (BANANAS.clone(), 100 * BASE_FACTOR),
+                        (BASE_TOKEN.clone(), 200 * BASE_FACTOR),
+                    ],
+                    ellipticoin::Address::PublicKey(*BOB) =>
+                    vec![(APPLES.clone(), 100 * BASE_FACTOR)],
+            },
+            &mut state,
+        );
+
+        native::create_pool(&mut api, APPLES.clone(), 100 * BASE_FACTOR, BASE_FACTOR).unwrap();
+        native::create_pool(&mut api, BANANAS.clone(), 100 * BASE_FACTOR, BASE_FACTOR).unwrap();
+
+        let quote = quote(&mut api, APPLES.clone(), BANANAS.clone(), 100 * BASE_FACTOR).unwrap();
+        assert_eq!(quote.base_token_amount, 49_924_888);
+        assert_eq!(quote.output_amount, 33_233_234);
+        assert_eq!(quote.input_token_fee, 300_000);
+        assert_eq!(quote.base_token_fee, 149_774);
+        assert_eq!(
+            get_pool_supply_of_token(&mut api, APPLES.clone()),
+            100 * BASE_FACTOR
+        );
+
+        api.caller = Address::PublicKey(BOB.clone());
+        native::exchange(
+            &mut api,
+            APPLES.clone(),
+            BANANAS.clone(),
+            100 * BASE_FACTOR,
+            0,
+        )
+        .unwrap();
+        assert_eq!(
+            token::get_balance(
+                &mut api,
+                BANANAS.clone(),
+                ellipticoin::Address::PublicKey(*BOB)
+            ),
+            quote.output_amount
+        );
+        assert_eq!(
+            get_pool_supply_of_base_token(&mut api, BANANAS.clone()),
+            100 * BASE_FACTOR + quote.base_token_amount
+        );
+    }
+
+    #[test]
+    fn test_quote_pool_not_found() {
+        let mut state = HashMap::new();
+        let mut api = setup(hashmap! {}, &mut state);
+        assert_eq!(
+            quote(&mut api, APPLES.clone(), BASE_TOKEN.clone(), BASE_FACTOR)
+                .unwrap_err()
+                .code,
+            errors::POOL_NOT_FOUND.code
+        );
+    }
+
+    #[test]
+    fn test_price_in_base_token_pool_not_found() {
+        let mut state = HashMap::new();
+        let mut api = setup(hashmap! {}, &mut state);
+        assert_eq!(
+            price_in_base_token(&mut api, APPLES.clone())
+                .unwrap_err()
+                .code,
+            errors::POOL_NOT_FOUND.code
+        );
+        assert_eq!(
+            price_in_base_token(&mut api, BASE_TOKEN.clone()).unwrap(),
+            BASE_FACTOR
+        );
+    }
+
     #[test]
     fn test_exchange_invariant_overflow() {
         let mut state = HashMap::new();
@@ -835,7 +1237,7 @@ mod tests {
 
         native::create_pool(&mut api, APPLES.clone(), 100 * BASE_FACTOR, BASE_FACTOR).unwrap();
         api.caller = Address::PublicKey(BOB.clone());
-        credit_pool_supply_of_base_token(&mut api, BASE_TOKEN.clone(), 100 * BASE_FACTOR);
+        credit_pool_supply_of_base_token(&mut api, BASE_TOKEN.clone(), 100 * BASE_FACTOR).unwrap();
 
         native::exchange(
             &mut api,
@@ -1,5 +1,9 @@
-use crate::{error::CONTRACT_NOT_FOUND, transaction::TransactionRequest};
+use crate::{
+    constants::ACTIVATION_HEIGHT, error::CONTRACT_NOT_FOUND, helpers::sha256,
+    transaction::TransactionRequest,
+};
 use serde_cbor::{value::from_value, Value};
+use std::convert::TryInto;
 use wasm_rpc::error::Error;
 
 #[macro_use]
@@ -11,10 +15,52 @@ pub mod exchange;
 pub mod test_api;
 pub mod token;
 
+pub fn run_in_block<API: ::ellipticoin::API>(
+    api: &mut API,
+    transaction: TransactionRequest,
+    block_number: u32,
+) -> serde_cbor::Value {
+    if block_number < *ACTIVATION_HEIGHT {
+        run_unvalidated(api, transaction)
+    } else {
+        run(api, transaction)
+    }
+}
+
 pub fn run<API: ::ellipticoin::API>(
     api: &mut API,
     transaction: TransactionRequest,
 ) -> serde_cbor::Value {
+    if let Err(error) = ellipticoin::validate_network_id(transaction.network_id) {
+        return serde_cbor::value::to_value(Err::<(), Box<Error>>(error)).unwrap();
+    }
+    if let Err(error) = ellipticoin::validate_nonce(api, transaction.nonce) {
+        return serde_cbor::value::to_value(Err::<(), ellipticoin::InvalidNonce>(error)).unwrap();
+    }
+    let return_value = call(api, &transaction);
+    // Failed transactions still use up their nonce so they can't be replayed
+    ellipticoin::increment_nonce(api);
+    ::ellipticoin::API::commit(api);
+    return_value
+}
+
+// Transactions in blocks below ACTIVATION_HEIGHT were sealed before nonces
+// were enforced so they're run without validation. The sender's nonce is
+// advanced to the transaction's nonce.
+pub fn run_unvalidated<API: ::ellipticoin::API>(
+    api: &mut API,
+    transaction: TransactionRequest,
+) -> serde_cbor::Value {
+    let return_value = call(api, &transaction);
+    let sender = ::ellipticoin::Address::PublicKey(transaction.sender);
+    if transaction.nonce > ellipticoin::get_nonce(api, sender.clone()) {
+        ellipticoin::set_nonce(api, sender, transaction.nonce);
+    }
+    ::ellipticoin::API::commit(api);
+    return_value
+}
+
+fn call<API: ::ellipticoin::API>(api: &mut API, transaction: &TransactionRequest) -> Value {
     let f = match &transaction.contract[..] {
         "Bridge" => bridge::native::call,
         "Ellipticoin" => ellipticoin::native::call,
@@ -33,8 +79,88 @@ pub fn run<API: ::ellipticoin::API>(
         Ok(Err(_))
     ) {
         ::ellipticoin::API::revert(api);
-    } else {
-        ::ellipticoin::API::commit(api);
-    };
+    }
     return_value
 }
+
+pub const CONTRACT_NAMES: [&str; 4] = [
+    bridge::CONTRACT_NAME,
+    ellipticoin::CONTRACT_NAME,
+    exchange::CONTRACT_NAME,
+    token::CONTRACT_NAME,
+];
+
+// Decodes the last part of a state key. Used by the iterators generated by
+// `state_accessors!`.
+pub trait FromKey: Sized {
+    fn from_key(key: &[u8]) -> Option<Self>;
+}
+
+impl FromKey for ::ellipticoin::Address {
+    fn from_key(key: &[u8]) -> Option<Self> {
+        if let Ok(public_key) = key.try_into() {
+            return Some(::ellipticoin::Address::PublicKey(public_key));
+        }
+        CONTRACT_NAMES
+            .iter()
+            .find(|contract_name| contract_name.as_bytes() == key)
+            .map(|contract_name| ::ellipticoin::Address::Contract(contract_name.to_string()))
+    }
+}
+
+pub fn decode_key(key: &[u8]) -> Option<(&'static str, &'static str, Vec<u8>)> {
+    let contracts: [(&'static str, &'static [&'static str]); 3] = [
+        (ellipticoin::CONTRACT_NAME, ellipticoin::NAMESPACES),
+        (exchange::CONTRACT_NAME, exchange::NAMESPACES),
+        (token::CONTRACT_NAME, token::NAMESPACES),
+    ];
+    let (contract_name, namespaces) = contracts
+        .iter()
+        .find(|(contract_name, _)| key.starts_with(&sha256(contract_name.as_bytes().to_vec())))?;
+    let namespace = namespaces.get(*key.get(32)? as usize)?;
+    Some((*contract_name, *namespace, key[33..].to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(contract_name: &str, namespace: u8, key: &[u8]) -> Vec<u8> {
+        [
+            sha256(contract_name.as_bytes().to_vec()).to_vec(),
+            vec![namespace],
+            key.to_vec(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_decode_key() {
+        assert_eq!(
+            decode_key(&key(
+                token::CONTRACT_NAME,
+                token::Namespace::TotalSupply as u8,
+                &[1, 2]
+            )),
+            Some((token::CONTRACT_NAME, "total_supply", vec![1, 2]))
+        );
+        assert_eq!(
+            decode_key(&key(
+                ellipticoin::CONTRACT_NAME,
+                ellipticoin::Namespace::BlockNumber as u8,
+                &[]
+            )),
+            Some((ellipticoin::CONTRACT_NAME, "block_number", vec![]))
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid_key() {
+        assert_eq!(decode_key(&key("Unknown", 0, &[1])), None);
+        assert_eq!(decode_key(&key(token::CONTRACT_NAME, 255, &[1])), None);
+        assert_eq!(
+            decode_key(&sha256(token::CONTRACT_NAME.as_bytes().to_vec())),
+            None
+        );
+    }
+}
@@ -1,12 +1,18 @@
 use crate::transaction::TransactionRequest;
-use ellipticoin::Address;
-use std::{collections::HashMap, convert::TryInto};
+use ellipticoin::{Address, Event};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryInto,
+};
 
 pub struct TestAPI<'a> {
     pub state: &'a mut HashMap<Vec<u8>, Vec<u8>>,
     pub contract: String,
     pub transaction: TransactionRequest,
     pub transaction_state: HashMap<Vec<u8>, Vec<u8>>,
+    pub checkpoints: Vec<(HashMap<Vec<u8>, Vec<u8>>, usize)>,
+    pub transaction_events: Vec<Event>,
+    pub events: Vec<Event>,
     pub sender: [u8; 32],
     pub caller: Address,
 }
@@ -30,6 +36,9 @@ impl<'a> TestAPI<'a> {
             contract,
             transaction: transaction.clone(),
             transaction_state: HashMap::new(),
+            checkpoints: vec![],
+            transaction_events: vec![],
+            events: vec![],
             caller: Address::PublicKey(transaction.sender),
             sender: transaction.sender.try_into().unwrap(),
         }
@@ -47,12 +56,46 @@ impl<'a> ellipticoin::StateAPI for TestAPI<'a> {
         self.transaction_state.insert(key.to_vec(), value.to_vec());
     }
 
+    fn scan(&mut self, prefix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut entries: BTreeMap<Vec<u8>, Vec<u8>> = self
+            .state
+            .iter()
+            .chain(self.transaction_state.iter())
+            .filter(|(key, _value)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        entries.retain(|_key, value| !value.is_empty());
+        entries.into_iter().collect()
+    }
+
     fn commit(&mut self) {
+        self.checkpoints.clear();
         self.state.extend(self.transaction_state.clone());
+        self.events.append(&mut self.transaction_events);
     }
 
     fn revert(&mut self) {
         self.transaction_state.clear();
+        self.transaction_events.clear();
+        self.checkpoints.clear();
+    }
+
+    fn checkpoint(&mut self) {
+        self.checkpoints.push((
+            self.transaction_state.clone(),
+            self.transaction_events.len(),
+        ));
+    }
+
+    fn rollback(&mut self) {
+        if let Some((transaction_state, events_len)) = self.checkpoints.pop() {
+            self.transaction_state = transaction_state;
+            self.transaction_events.truncate(events_len);
+        }
+    }
+
+    fn release(&mut self) {
+        self.checkpoints.pop();
     }
 }
 
@@ -60,4 +103,8 @@ impl<'a> ellipticoin::API for TestAPI<'a> {
     fn caller(&self) -> Address {
         self.caller.clone()
     }
+
+    fn emit_event(&mut self, event: Event) {
+        self.transaction_events.push(event);
+    }
 }
@@ -1,4 +1,4 @@
-use crate::system_contracts::bridge;
+use crate::system_contracts::{bridge, token::Metadata};
 use ellipticoin::Token;
 
 lazy_static! {
@@ -21,4 +21,42 @@ lazy_static! {
             .unwrap()
             .into()
     );
+    pub static ref METADATA: Vec<(Token, Metadata)> = vec![
+        (
+            ELC.clone(),
+            Metadata {
+                name: "Ellipticoin".to_string(),
+                symbol: "ELC".to_string(),
+                decimals: 6,
+                website: "https://ellipticoin.org".to_string(),
+            }
+        ),
+        (
+            DAI.clone(),
+            Metadata {
+                name: "Dai".to_string(),
+                symbol: "DAI".to_string(),
+                decimals: 6,
+                website: "https://makerdao.com".to_string(),
+            }
+        ),
+        (
+            BTC.clone(),
+            Metadata {
+                name: "Bitcoin".to_string(),
+                symbol: "BTC".to_string(),
+                decimals: 6,
+                website: "https://bitcoin.org".to_string(),
+            }
+        ),
+        (
+            ETH.clone(),
+            Metadata {
+                name: "Ethereum".to_string(),
+                symbol: "ETH".to_string(),
+                decimals: 6,
+                website: "https://ethereum.org".to_string(),
+            }
+        ),
+    ];
 }
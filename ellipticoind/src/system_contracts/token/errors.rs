@@ -11,6 +11,18 @@ lazy_static! {
     };
     pub static ref NOT_TOKEN_ISSUER: Error = Error {
         code: 3,
-        message: "Only the token issuer can issue tokens".to_string(),
+        message: "Only the token's issuer can do this".to_string(),
+    };
+    pub static ref INVALID_VESTING_SCHEDULE: Error = Error {
+        code: 9,
+        message: "Vesting schedules must have a cliff between their start and end".to_string(),
+    };
+    pub static ref TOKEN_PAUSED: Error = Error {
+        code: 10,
+        message: "Transfers of this token have been paused".to_string(),
+    };
+    pub static ref ACCOUNT_FROZEN: Error = Error {
+        code: 11,
+        message: "This account has been frozen".to_string(),
     };
 }
@@ -0,0 +1,61 @@
+use ellipticoin::{Address, Token, VersionedU128};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct Transfer {
+    pub token: Token,
+    pub from: Address,
+    pub to: Address,
+    pub amount: VersionedU128,
+}
+
+#[derive(Serialize)]
+pub struct Approval {
+    pub token: Token,
+    pub owner: Address,
+    pub spender: Address,
+    pub amount: VersionedU128,
+}
+
+#[derive(Serialize)]
+pub struct Mint {
+    pub token: Token,
+    pub to: Address,
+    pub amount: VersionedU128,
+}
+
+#[derive(Serialize)]
+pub struct Burn {
+    pub token: Token,
+    pub from: Address,
+    pub amount: VersionedU128,
+}
+
+#[derive(Serialize)]
+pub struct Locked {
+    pub token: Token,
+    pub from: Address,
+    pub to: Address,
+    pub amount: VersionedU128,
+    pub start_block: u32,
+    pub cliff_block: u32,
+    pub end_block: u32,
+}
+
+#[derive(Serialize)]
+pub struct Paused {
+    pub token: Token,
+}
+
+#[derive(Serialize)]
+pub struct Frozen {
+    pub token: Token,
+    pub address: Address,
+}
+
+#[derive(Serialize)]
+pub struct Released {
+    pub token: Token,
+    pub to: Address,
+    pub amount: VersionedU128,
+}
@@ -1,28 +1,154 @@
 pub mod constants;
 pub mod errors;
+mod events;
+pub mod vesting;
 
-use ellipticoin::{state_accessors, Address, Bytes, Token};
+use crate::system_contracts::ellipticoin::get_block_number;
+use ellipticoin::{
+    helpers::{checked_add, checked_sub},
+    state_accessors, Address, Bytes, Token, VersionedU128,
+};
 use errors::Error;
+use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
+use vesting::Lock;
 use wasm_rpc_macros::export_native;
 
-pub const BASE_FACTOR: u64 = 1_000_000;
-const CONTRACT_NAME: &'static str = "Token";
+pub const BASE_FACTOR: u128 = 1_000_000;
+pub const CONTRACT_NAME: &'static str = "Token";
 
 state_accessors!(
-    balance(token: Token, address: Address) -> u64;
-    total_supply(token: Token) -> u64;
+    balance(token: Token, address: Address) -> u128 as VersionedU128;
+    total_supply(token: Token) -> u128 as VersionedU128;
+    allowance(token: Token, owner: Address, spender: Address) -> u128 as VersionedU128;
+    metadata(token: Token) -> Option<Metadata>;
+    registered_tokens() -> Vec<Token>;
+    locks(token: Token, address: Address) -> Vec<Lock>;
+    paused(token: Token) -> bool;
+    frozen(token: Token, address: Address) -> bool;
 );
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Metadata {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub website: String,
+}
+
 export_native! {
     pub fn transfer<API: ellipticoin::API>(
         api: &mut API,
         token: Token,
         to: Bytes,
-        amount: u64,
+        amount: u128,
+    ) -> Result<(), Box<Error>> {
+        super::transfer(api, token, api.caller(), to.try_into()?, amount)
+    }
+
+    pub fn transfer_from<API: ellipticoin::API>(
+        api: &mut API,
+        token: Token,
+        from: Bytes,
+        to: Bytes,
+        amount: u128,
+    ) -> Result<(), Box<Error>> {
+        let from: Address = from.try_into()?;
+        let allowance = get_allowance(api, token.clone(), from.clone(), api.caller());
+        if amount > allowance {
+            return Err(Box::new(errors::INSUFFICIENT_ALLOWANCE.clone()));
+        }
+        super::transfer(api, token.clone(), from.clone(), to.try_into()?, amount)?;
+        set_allowance(api, token, from, api.caller(), allowance - amount);
+        Ok(())
+    }
+
+    pub fn approve<API: ellipticoin::API>(
+        api: &mut API,
+        token: Token,
+        spender: Bytes,
+        amount: u128,
+    ) -> Result<(), Box<Error>> {
+        super::approve(api, token, spender.try_into()?, amount);
+        Ok(())
+    }
+
+    pub fn increase_allowance<API: ellipticoin::API>(
+        api: &mut API,
+        token: Token,
+        spender: Bytes,
+        amount: u128,
+    ) -> Result<(), Box<Error>> {
+        let spender: Address = spender.try_into()?;
+        let allowance = get_allowance(api, token.clone(), api.caller(), spender.clone());
+        super::approve(api, token, spender, checked_add(allowance, amount)?);
+        Ok(())
+    }
+
+    pub fn decrease_allowance<API: ellipticoin::API>(
+        api: &mut API,
+        token: Token,
+        spender: Bytes,
+        amount: u128,
+    ) -> Result<(), Box<Error>> {
+        let spender: Address = spender.try_into()?;
+        let allowance = get_allowance(api, token.clone(), api.caller(), spender.clone());
+        if amount > allowance {
+            return Err(Box::new(errors::INSUFFICIENT_ALLOWANCE.clone()));
+        }
+        super::approve(api, token, spender, allowance - amount);
+        Ok(())
+    }
+
+    pub fn set_metadata<API: ellipticoin::API>(
+        api: &mut API,
+        token: Token,
+        name: String,
+        symbol: String,
+        decimals: u8,
+        website: String,
+    ) -> Result<(), Box<Error>> {
+        if token.issuer != api.caller() {
+            return Err(Box::new(errors::NOT_TOKEN_ISSUER.clone()));
+        }
+        super::update_metadata(api, token, Metadata {
+            name,
+            symbol,
+            decimals,
+            website,
+        });
+        Ok(())
+    }
+
+    pub fn pause<API: ellipticoin::API>(api: &mut API, token: Token) -> Result<(), Box<Error>> {
+        validate_issuer(api, &token)?;
+        super::update_paused(api, token, true);
+        Ok(())
+    }
+
+    pub fn unpause<API: ellipticoin::API>(api: &mut API, token: Token) -> Result<(), Box<Error>> {
+        validate_issuer(api, &token)?;
+        super::update_paused(api, token, false);
+        Ok(())
+    }
+
+    pub fn freeze<API: ellipticoin::API>(
+        api: &mut API,
+        token: Token,
+        address: Bytes,
+    ) -> Result<(), Box<Error>> {
+        validate_issuer(api, &token)?;
+        super::update_frozen(api, token, address.try_into()?, true);
+        Ok(())
+    }
+
+    pub fn unfreeze<API: ellipticoin::API>(
+        api: &mut API,
+        token: Token,
+        address: Bytes,
     ) -> Result<(), Box<Error>> {
-        debit(api, token.clone(), api.caller(), amount.clone())?;
-        credit(api, token, to.try_into()?, amount);
+        validate_issuer(api, &token)?;
+        super::update_frozen(api, token, address.try_into()?, false);
         Ok(())
     }
 
@@ -30,7 +156,7 @@ export_native! {
         api: &mut API,
         token_id: Bytes,
         address: Bytes,
-        amount: u64,
+        amount: u128,
     ) -> Result<(), Box<Error>> {
         super::mint(
             api,
@@ -47,7 +173,7 @@ export_native! {
         api: &mut API,
         token_id: Bytes,
         address: Address,
-        amount: u64,
+        amount: u128,
     ) -> Result<(), Box<Error>> {
         super::burn(
             api,
@@ -60,16 +186,166 @@ export_native! {
         )
     }
 
+    pub fn lock<API: ellipticoin::API>(
+        api: &mut API,
+        token: Token,
+        address: Bytes,
+        amount: u128,
+        start_block: u32,
+        cliff_block: u32,
+        end_block: u32,
+    ) -> Result<(), Box<Error>> {
+        validate_issuer(api, &token)?;
+        let address: Address = address.try_into()?;
+        let lock = Lock::new(amount, start_block, cliff_block, end_block)?;
+        super::transfer(api, token.clone(), api.caller(), Address::Contract(CONTRACT_NAME.to_string()), amount)?;
+        let mut locks = get_locks(api, token.clone(), address.clone());
+        locks.push(lock);
+        set_locks(api, token.clone(), address.clone(), locks);
+        api.emit(CONTRACT_NAME, "Locked", vec![api.caller(), address.clone()], events::Locked {
+            token,
+            from: api.caller(),
+            to: address,
+            amount: amount.into(),
+            start_block,
+            cliff_block,
+            end_block,
+        });
+        Ok(())
+    }
+
+    pub fn release<API: ellipticoin::API>(
+        api: &mut API,
+        token: Token,
+    ) -> Result<(), Box<Error>> {
+        let block_number = get_block_number(api);
+        let mut locks = get_locks(api, token.clone(), api.caller());
+        let mut amount = 0;
+        for lock in locks.iter_mut() {
+            let releasable = lock.releasable(block_number)?;
+            lock.released = checked_add(lock.released.0, releasable)?.into();
+            amount = checked_add(amount, releasable)?;
+        }
+        locks.retain(|lock| !lock.is_fully_released());
+        set_locks(api, token.clone(), api.caller(), locks);
+        super::transfer(api, token.clone(), Address::Contract(CONTRACT_NAME.to_string()), api.caller(), amount)?;
+        api.emit(CONTRACT_NAME, "Released", vec![api.caller()], events::Released {
+            token,
+            to: api.caller(),
+            amount: amount.into(),
+        });
+        Ok(())
+    }
 }
-pub fn transfer_from<API: ellipticoin::API>(
+
+pub fn transfer<API: ellipticoin::API>(
     api: &mut API,
     token: Token,
     sender: Address,
     recipient: Address,
-    amount: u64,
+    amount: u128,
+) -> Result<(), Box<Error>> {
+    validate_transferable(api, &token, &sender, &recipient)?;
+    debit(api, token.clone(), sender.clone(), amount)?;
+    credit(api, token.clone(), recipient.clone(), amount)?;
+    api.emit(
+        CONTRACT_NAME,
+        "Transfer",
+        vec![sender.clone(), recipient.clone()],
+        events::Transfer {
+            token,
+            from: sender,
+            to: recipient,
+            amount: amount.into(),
+        },
+    );
+    Ok(())
+}
+
+pub fn approve<API: ellipticoin::API>(api: &mut API, token: Token, spender: Address, amount: u128) {
+    set_allowance(api, token.clone(), api.caller(), spender.clone(), amount);
+    api.emit(
+        CONTRACT_NAME,
+        "Approval",
+        vec![api.caller(), spender.clone()],
+        events::Approval {
+            token,
+            owner: api.caller(),
+            spender,
+            amount: amount.into(),
+        },
+    );
+}
+
+// Callers are responsible for checking that the change was made by the issuer
+pub fn update_metadata<API: ellipticoin::API>(api: &mut API, token: Token, metadata: Metadata) {
+    if get_metadata(api, token.clone()).is_none() {
+        let mut registered_tokens = get_registered_tokens(api);
+        registered_tokens.push(token.clone());
+        set_registered_tokens(api, registered_tokens);
+    }
+    set_metadata(api, token, Some(metadata));
+}
+
+// Run at genesis. Tokens issued by contracts have no issuer that could call
+// `set_metadata`, so their metadata is seeded from `constants::METADATA`.
+pub fn seed_metadata<API: ellipticoin::API>(api: &mut API) {
+    for (token, metadata) in constants::METADATA.iter() {
+        if get_metadata(api, token.clone()).is_none() {
+            update_metadata(api, token.clone(), metadata.clone());
+        }
+    }
+}
+
+// Callers are responsible for checking that the change was made by the issuer
+pub fn update_paused<API: ellipticoin::API>(api: &mut API, token: Token, paused: bool) {
+    set_paused(api, token.clone(), paused);
+    api.emit(
+        CONTRACT_NAME,
+        if paused { "Paused" } else { "Unpaused" },
+        vec![],
+        events::Paused { token },
+    );
+}
+
+// Callers are responsible for checking that the change was made by the issuer
+pub fn update_frozen<API: ellipticoin::API>(
+    api: &mut API,
+    token: Token,
+    address: Address,
+    frozen: bool,
+) {
+    set_frozen(api, token.clone(), address.clone(), frozen);
+    api.emit(
+        CONTRACT_NAME,
+        if frozen { "Frozen" } else { "Unfrozen" },
+        vec![address.clone()],
+        events::Frozen { token, address },
+    );
+}
+
+fn validate_issuer<API: ellipticoin::API>(api: &mut API, token: &Token) -> Result<(), Box<Error>> {
+    if token.issuer == api.caller() {
+        Ok(())
+    } else {
+        Err(Box::new(errors::NOT_TOKEN_ISSUER.clone()))
+    }
+}
+
+pub fn validate_transferable<API: ellipticoin::API>(
+    api: &mut API,
+    token: &Token,
+    sender: &Address,
+    recipient: &Address,
 ) -> Result<(), Box<Error>> {
-    debit(api, token.clone(), sender, amount.clone())?;
-    credit(api, token.clone(), recipient.clone(), amount);
+    if get_paused(api, token.clone()) {
+        return Err(Box::new(errors::TOKEN_PAUSED.clone()));
+    }
+    if get_frozen(api, token.clone(), sender.clone())
+        || get_frozen(api, token.clone(), recipient.clone())
+    {
+        return Err(Box::new(errors::ACCOUNT_FROZEN.clone()));
+    }
     Ok(())
 }
 
@@ -77,11 +353,24 @@ pub fn mint<API: ellipticoin::API>(
     api: &mut API,
     token: Token,
     to: Address,
-    amount: u64,
+    amount: u128,
 ) -> Result<(), Box<Error>> {
-    credit(api, token.clone(), to, amount);
+    if get_frozen(api, token.clone(), to.clone()) {
+        return Err(Box::new(errors::ACCOUNT_FROZEN.clone()));
+    }
+    credit(api, token.clone(), to.clone(), amount)?;
     let total_supply = get_total_supply(api, token.clone());
-    set_total_supply(api, token, total_supply + amount);
+    set_total_supply(api, token.clone(), checked_add(total_supply, amount)?);
+    api.emit(
+        CONTRACT_NAME,
+        "Mint",
+        vec![to.clone()],
+        events::Mint {
+            token,
+            to,
+            amount: amount.into(),
+        },
+    );
     Ok(())
 }
 
@@ -89,24 +378,46 @@ pub fn burn<API: ellipticoin::API>(
     api: &mut API,
     token: Token,
     to: Address,
-    amount: u64,
+    amount: u128,
 ) -> Result<(), Box<Error>> {
-    debit(api, token.clone(), to, amount)?;
+    if get_paused(api, token.clone()) {
+        return Err(Box::new(errors::TOKEN_PAUSED.clone()));
+    }
+    if get_frozen(api, token.clone(), to.clone()) {
+        return Err(Box::new(errors::ACCOUNT_FROZEN.clone()));
+    }
+    debit(api, token.clone(), to.clone(), amount)?;
     let total_supply = get_total_supply(api, token.clone());
-    set_total_supply(api, token, total_supply - amount);
+    set_total_supply(api, token.clone(), checked_sub(total_supply, amount)?);
+    api.emit(
+        CONTRACT_NAME,
+        "Burn",
+        vec![to.clone()],
+        events::Burn {
+            token,
+            from: to,
+            amount: amount.into(),
+        },
+    );
     Ok(())
 }
 
-pub fn credit<API: ellipticoin::API>(api: &mut API, token: Token, address: Address, amount: u64) {
+pub fn credit<API: ellipticoin::API>(
+    api: &mut API,
+    token: Token,
+    address: Address,
+    amount: u128,
+) -> Result<(), Box<Error>> {
     let balance = get_balance(api, token.clone(), address.clone());
-    set_balance(api, token.clone(), address, balance + amount)
+    set_balance(api, token.clone(), address, checked_add(balance, amount)?);
+    Ok(())
 }
 
 pub fn debit<API: ellipticoin::API>(
     api: &mut API,
     token: Token,
     address: Address,
-    amount: u64,
+    amount: u128,
 ) -> Result<(), Box<Error>> {
     let balance = get_balance(api, token.clone(), address.clone());
     if amount <= balance {
@@ -116,11 +427,34 @@ pub fn debit<API: ellipticoin::API>(
     }
 }
 
+pub fn get_locked_balance<API: ellipticoin::API>(
+    api: &mut API,
+    token: Token,
+    address: Address,
+) -> u128 {
+    get_locks(api, token, address)
+        .iter()
+        .fold(0, |total, lock| total.saturating_add(lock.locked()))
+}
+
+pub fn get_vested_balance<API: ellipticoin::API>(
+    api: &mut API,
+    token: Token,
+    address: Address,
+) -> Result<u128, Box<Error>> {
+    let block_number = get_block_number(api);
+    get_locks(api, token, address)
+        .iter()
+        .try_fold(0, |total, lock| {
+            checked_add(total, lock.releasable(block_number)?)
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::{native, *};
-    use crate::system_contracts::test_api::TestAPI;
-    use ellipticoin::Bytes;
+    use crate::system_contracts::{ellipticoin::set_block_number, test_api::TestAPI};
+    use ellipticoin::{Bytes, API};
     use std::{collections::HashMap, env};
 
     use ellipticoin_test_framework::constants::actors::{ALICE, ALICES_PRIVATE_KEY, BOB, CAROL};
@@ -146,6 +480,65 @@ mod tests {
             20
         );
     }
+    #[test]
+    fn test_transfer_larger_than_u64() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        native::mint(&mut api, TOKEN.id.clone(), Bytes(ALICE.to_vec()), u128::MAX).unwrap();
+        native::transfer(
+            &mut api,
+            TOKEN.clone(),
+            BOB.to_vec().into(),
+            u64::MAX as u128 + 1,
+        )
+        .unwrap();
+        assert_eq!(
+            get_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB)),
+            u64::MAX as u128 + 1
+        );
+        assert_eq!(get_total_supply(&mut api, TOKEN.clone()), u128::MAX);
+    }
+
+    #[test]
+    fn test_legacy_balance() {
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        api.set_state(
+            CONTRACT_NAME,
+            [
+                vec![Namespace::Balance as u8],
+                TOKEN.clone().into(),
+                Address::PublicKey(*ALICE).into(),
+            ]
+            .concat(),
+            100u64,
+        );
+        assert_eq!(
+            get_balance(&mut api, TOKEN.clone(), Address::PublicKey(*ALICE)),
+            100
+        );
+    }
+
+    #[test]
+    fn test_transfer_emits_event() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*ALICE), 100);
+        native::transfer(&mut api, TOKEN.clone(), BOB.to_vec().into(), 20).unwrap();
+        assert_eq!(api.transaction_events.len(), 1);
+        let event = &api.transaction_events[0];
+        assert_eq!(event.contract, "Token");
+        assert_eq!(event.name, "Transfer");
+        assert_eq!(
+            event.addresses,
+            vec![Address::PublicKey(*ALICE), Address::PublicKey(*BOB)]
+        );
+        assert!(native::transfer(&mut api, TOKEN.clone(), BOB.to_vec().into(), 120).is_err());
+        assert_eq!(api.transaction_events.len(), 1);
+    }
+
     #[test]
     fn test_transfer_insufficient_funds() {
         env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
@@ -162,15 +555,43 @@ mod tests {
         env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
         let mut state = HashMap::new();
         let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
-        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB), 100);
-        assert!(transfer_from(
-            &mut api,
-            TOKEN.clone(),
-            Address::PublicKey(*BOB),
-            Address::PublicKey(*CAROL),
-            120
-        )
-        .is_err());
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*ALICE), 100);
+        native::approve(&mut api, TOKEN.clone(), BOB.to_vec().into(), 120).unwrap();
+        api.caller = Address::PublicKey(*BOB);
+        assert_eq!(
+            native::transfer_from(
+                &mut api,
+                TOKEN.clone(),
+                ALICE.to_vec().into(),
+                CAROL.to_vec().into(),
+                120
+            )
+            .unwrap_err()
+            .code,
+            errors::INSUFFICIENT_FUNDS.code
+        );
+    }
+
+    #[test]
+    fn test_transfer_from_insufficient_allowance() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*ALICE), 100);
+        native::approve(&mut api, TOKEN.clone(), BOB.to_vec().into(), 10).unwrap();
+        api.caller = Address::PublicKey(*BOB);
+        assert_eq!(
+            native::transfer_from(
+                &mut api,
+                TOKEN.clone(),
+                ALICE.to_vec().into(),
+                CAROL.to_vec().into(),
+                20
+            )
+            .unwrap_err()
+            .code,
+            errors::INSUFFICIENT_ALLOWANCE.code
+        );
     }
 
     #[test]
@@ -184,13 +605,14 @@ mod tests {
             Address::PublicKey(*ALICE),
             100,
         );
+        native::approve(&mut api, TOKEN.clone(), BOB.to_vec().into(), 30).unwrap();
         api.sender = *BOB;
         api.caller = Address::PublicKey(*BOB);
-        transfer_from(
+        native::transfer_from(
             &mut api,
             TOKEN.clone(),
-            Address::PublicKey(*ALICE),
-            Address::PublicKey(*CAROL),
+            ALICE.to_vec().into(),
+            CAROL.to_vec().into(),
             20,
         )
         .unwrap();
@@ -202,7 +624,439 @@ mod tests {
             get_balance(&mut api, TOKEN.clone(), Address::PublicKey(*CAROL)),
             20
         );
+        assert_eq!(
+            get_allowance(
+                &mut api,
+                TOKEN.clone(),
+                Address::PublicKey(*ALICE),
+                Address::PublicKey(*BOB)
+            ),
+            10
+        );
+    }
+
+    #[test]
+    fn test_increase_and_decrease_allowance() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        native::approve(&mut api, TOKEN.clone(), BOB.to_vec().into(), 10).unwrap();
+        native::increase_allowance(&mut api, TOKEN.clone(), BOB.to_vec().into(), 5).unwrap();
+        native::decrease_allowance(&mut api, TOKEN.clone(), BOB.to_vec().into(), 3).unwrap();
+        assert_eq!(
+            get_allowance(
+                &mut api,
+                TOKEN.clone(),
+                Address::PublicKey(*ALICE),
+                Address::PublicKey(*BOB)
+            ),
+            12
+        );
+        assert_eq!(
+            native::decrease_allowance(&mut api, TOKEN.clone(), BOB.to_vec().into(), 13)
+                .unwrap_err()
+                .code,
+            errors::INSUFFICIENT_ALLOWANCE.code
+        );
+    }
+
+    #[test]
+    fn test_lock_and_release() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*ALICE), 1000);
+        native::lock(
+            &mut api,
+            TOKEN.clone(),
+            BOB.to_vec().into(),
+            1000,
+            100,
+            150,
+            200,
+        )
+        .unwrap();
+        assert_eq!(
+            get_balance(&mut api, TOKEN.clone(), Address::PublicKey(*ALICE)),
+            0
+        );
+        assert_eq!(
+            get_locked_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB)),
+            1000
+        );
+
+        api.caller = Address::PublicKey(*BOB);
+        set_block_number(&mut api, 149);
+        native::release(&mut api, TOKEN.clone()).unwrap();
+        assert_eq!(
+            get_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB)),
+            0
+        );
+
+        set_block_number(&mut api, 175);
+        assert_eq!(
+            get_vested_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB)).unwrap(),
+            750
+        );
+        native::release(&mut api, TOKEN.clone()).unwrap();
+        assert_eq!(
+            get_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB)),
+            750
+        );
+        assert_eq!(
+            get_locked_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB)),
+            250
+        );
+        assert_eq!(
+            native::transfer(&mut api, TOKEN.clone(), CAROL.to_vec().into(), 751)
+                .unwrap_err()
+                .code,
+            errors::INSUFFICIENT_FUNDS.code
+        );
+
+        set_block_number(&mut api, 200);
+        native::release(&mut api, TOKEN.clone()).unwrap();
+        assert_eq!(
+            get_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB)),
+            1000
+        );
+        assert!(get_locks(&mut api, TOKEN.clone(), Address::PublicKey(*BOB)).is_empty());
+    }
+
+    #[test]
+    fn test_release_overflow() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *BOB, "Token".to_string());
+        let lock = Lock::new(u128::MAX, 0, 0, 0).unwrap();
+        set_locks(
+            &mut api,
+            TOKEN.clone(),
+            Address::PublicKey(*BOB),
+            vec![lock.clone(), lock],
+        );
+        assert_eq!(
+            native::release(&mut api, TOKEN.clone()).unwrap_err().code,
+            ellipticoin::errors::ARITHMETIC_OVERFLOW.code
+        );
+        assert_eq!(
+            get_vested_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB))
+                .unwrap_err()
+                .code,
+            ellipticoin::errors::ARITHMETIC_OVERFLOW.code
+        );
+        assert_eq!(
+            get_locked_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB)),
+            u128::MAX
+        );
+    }
+
+    #[test]
+    fn test_lock_not_issuer() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *BOB, "Token".to_string());
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB), 1000);
+        assert_eq!(
+            native::lock(
+                &mut api,
+                TOKEN.clone(),
+                CAROL.to_vec().into(),
+                1000,
+                100,
+                150,
+                200,
+            )
+            .unwrap_err()
+            .code,
+            errors::NOT_TOKEN_ISSUER.code
+        );
+        assert!(get_locks(&mut api, TOKEN.clone(), Address::PublicKey(*CAROL)).is_empty());
+        assert_eq!(
+            get_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB)),
+            1000
+        );
+    }
+
+    #[test]
+    fn test_lock_invalid_schedule() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*ALICE), 1000);
+        assert_eq!(
+            native::lock(
+                &mut api,
+                TOKEN.clone(),
+                BOB.to_vec().into(),
+                1000,
+                200,
+                150,
+                100,
+            )
+            .unwrap_err()
+            .code,
+            errors::INVALID_VESTING_SCHEDULE.code
+        );
+        assert_eq!(
+            get_balance(&mut api, TOKEN.clone(), Address::PublicKey(*ALICE)),
+            1000
+        );
+    }
+
+    #[test]
+    fn test_pause() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*ALICE), 100);
+        native::pause(&mut api, TOKEN.clone()).unwrap();
+        assert_eq!(
+            native::transfer(&mut api, TOKEN.clone(), BOB.to_vec().into(), 20)
+                .unwrap_err()
+                .code,
+            errors::TOKEN_PAUSED.code
+        );
+        native::unpause(&mut api, TOKEN.clone()).unwrap();
+        native::transfer(&mut api, TOKEN.clone(), BOB.to_vec().into(), 20).unwrap();
+        assert_eq!(
+            get_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB)),
+            20
+        );
+    }
+
+    #[test]
+    fn test_pause_not_issuer() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *BOB, "Token".to_string());
+        assert_eq!(
+            native::pause(&mut api, TOKEN.clone()).unwrap_err().code,
+            errors::NOT_TOKEN_ISSUER.code
+        );
+        assert_eq!(
+            native::freeze(&mut api, TOKEN.clone(), ALICE.to_vec().into())
+                .unwrap_err()
+                .code,
+            errors::NOT_TOKEN_ISSUER.code
+        );
+        assert!(!get_paused(&mut api, TOKEN.clone()));
+    }
+
+    #[test]
+    fn test_freeze() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*ALICE), 100);
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB), 100);
+        native::approve(&mut api, TOKEN.clone(), CAROL.to_vec().into(), 50).unwrap();
+        native::freeze(&mut api, TOKEN.clone(), BOB.to_vec().into()).unwrap();
+        assert_eq!(
+            native::transfer(&mut api, TOKEN.clone(), BOB.to_vec().into(), 20)
+                .unwrap_err()
+                .code,
+            errors::ACCOUNT_FROZEN.code
+        );
+
+        api.caller = Address::PublicKey(*CAROL);
+        assert_eq!(
+            native::transfer_from(
+                &mut api,
+                TOKEN.clone(),
+                ALICE.to_vec().into(),
+                BOB.to_vec().into(),
+                20
+            )
+            .unwrap_err()
+            .code,
+            errors::ACCOUNT_FROZEN.code
+        );
+
+        api.caller = Address::PublicKey(*BOB);
+        assert_eq!(
+            native::transfer(&mut api, TOKEN.clone(), CAROL.to_vec().into(), 20)
+                .unwrap_err()
+                .code,
+            errors::ACCOUNT_FROZEN.code
+        );
+
+        api.caller = Address::PublicKey(*ALICE);
+        native::unfreeze(&mut api, TOKEN.clone(), BOB.to_vec().into()).unwrap();
+        native::transfer(&mut api, TOKEN.clone(), BOB.to_vec().into(), 20).unwrap();
+        assert_eq!(
+            get_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB)),
+            120
+        );
+    }
+
+    #[test]
+    fn test_mint_to_frozen_account() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        native::freeze(&mut api, TOKEN.clone(), BOB.to_vec().into()).unwrap();
+        assert_eq!(
+            native::mint(&mut api, TOKEN.id.clone(), Bytes(BOB.to_vec()), 50)
+                .unwrap_err()
+                .code,
+            errors::ACCOUNT_FROZEN.code
+        );
+        assert_eq!(
+            get_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB)),
+            0
+        );
+        assert_eq!(get_total_supply(&mut api, TOKEN.clone()), 0);
+    }
+
+    #[test]
+    fn test_burn_from_frozen_account() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        native::mint(&mut api, TOKEN.id.clone(), Bytes(BOB.to_vec()), 50).unwrap();
+        native::freeze(&mut api, TOKEN.clone(), BOB.to_vec().into()).unwrap();
+        assert_eq!(
+            native::burn(&mut api, TOKEN.id.clone(), Address::PublicKey(*BOB), 50)
+                .unwrap_err()
+                .code,
+            errors::ACCOUNT_FROZEN.code
+        );
+        native::unfreeze(&mut api, TOKEN.clone(), BOB.to_vec().into()).unwrap();
+        native::pause(&mut api, TOKEN.clone()).unwrap();
+        assert_eq!(
+            native::burn(&mut api, TOKEN.id.clone(), Address::PublicKey(*BOB), 50)
+                .unwrap_err()
+                .code,
+            errors::TOKEN_PAUSED.code
+        );
+        assert_eq!(get_total_supply(&mut api, TOKEN.clone()), 50);
+    }
+
+    #[test]
+    fn test_set_metadata() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        native::set_metadata(
+            &mut api,
+            TOKEN.clone(),
+            "Apples".to_string(),
+            "APL".to_string(),
+            6,
+            "https://apples.example".to_string(),
+        )
+        .unwrap();
+        native::set_metadata(
+            &mut api,
+            TOKEN.clone(),
+            "Apples".to_string(),
+            "APPL".to_string(),
+            6,
+            "https://apples.example".to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            get_metadata(&mut api, TOKEN.clone()).unwrap().symbol,
+            "APPL"
+        );
+        assert_eq!(get_registered_tokens(&mut api), vec![TOKEN.clone()]);
+    }
+
+    #[test]
+    fn test_set_metadata_not_issuer() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *BOB, "Token".to_string());
+        assert_eq!(
+            native::set_metadata(
+                &mut api,
+                TOKEN.clone(),
+                "Apples".to_string(),
+                "APL".to_string(),
+                6,
+                "".to_string(),
+            )
+            .unwrap_err()
+            .code,
+            errors::NOT_TOKEN_ISSUER.code
+        );
+        assert!(get_metadata(&mut api, TOKEN.clone()).is_none());
+    }
+
+    #[test]
+    fn test_seed_metadata() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        let dai_metadata = Metadata {
+            name: "Dai Stablecoin".to_string(),
+            symbol: "DAI".to_string(),
+            decimals: 6,
+            website: "".to_string(),
+        };
+        update_metadata(&mut api, constants::DAI.clone(), dai_metadata.clone());
+        seed_metadata(&mut api);
+        seed_metadata(&mut api);
+        assert_eq!(
+            get_metadata(&mut api, constants::ELC.clone())
+                .unwrap()
+                .symbol,
+            "ELC"
+        );
+        assert_eq!(
+            get_metadata(&mut api, constants::DAI.clone()),
+            Some(dai_metadata)
+        );
+        assert_eq!(
+            get_registered_tokens(&mut api).len(),
+            constants::METADATA.len()
+        );
     }
+
+    #[test]
+    fn test_mint_overflow() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        native::mint(&mut api, TOKEN.id.clone(), Bytes(ALICE.to_vec()), u128::MAX).unwrap();
+        assert_eq!(
+            native::mint(&mut api, TOKEN.id.clone(), Bytes(BOB.to_vec()), 1)
+                .unwrap_err()
+                .code,
+            ellipticoin::errors::ARITHMETIC_OVERFLOW.code
+        );
+    }
+
+    #[test]
+    fn test_transfer_overflow() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*ALICE), 1);
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB), u128::MAX);
+        assert_eq!(
+            native::transfer(&mut api, TOKEN.clone(), BOB.to_vec().into(), 1)
+                .unwrap_err()
+                .code,
+            ellipticoin::errors::ARITHMETIC_OVERFLOW.code
+        );
+    }
+
+    #[test]
+    fn test_increase_allowance_overflow() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        native::approve(&mut api, TOKEN.clone(), BOB.to_vec().into(), u128::MAX).unwrap();
+        assert_eq!(
+            native::increase_allowance(&mut api, TOKEN.clone(), BOB.to_vec().into(), 1)
+                .unwrap_err()
+                .code,
+            ellipticoin::errors::ARITHMETIC_OVERFLOW.code
+        );
+    }
+
     #[test]
     fn test_mint() {
         env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
@@ -234,4 +1088,56 @@ mod tests {
         );
         assert_eq!(get_total_supply(&mut api, TOKEN.clone()), 50);
     }
+
+    #[test]
+    fn test_iter_balance() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        let other_token = Token {
+            issuer: Address::PublicKey(*BOB),
+            id: vec![].into(),
+        };
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*ALICE), 100);
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*BOB), 50);
+        set_balance(&mut api, other_token, Address::PublicKey(*CAROL), 25);
+        let mut expected_balances = vec![(ALICE.to_vec(), 100), (BOB.to_vec(), 50)];
+        expected_balances.sort();
+        assert_eq!(
+            iter_balance(&mut api, TOKEN.clone())
+                .unwrap()
+                .collect::<Vec<(Vec<u8>, u128)>>(),
+            expected_balances
+        );
+    }
+
+    #[test]
+    fn test_iter_balance_skips_tokens_with_longer_ids() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let mut state = HashMap::new();
+        let mut api = TestAPI::new(&mut state, *ALICE, "Token".to_string());
+        let longer_token = Token {
+            issuer: Address::PublicKey(*ALICE),
+            id: vec![1].into(),
+        };
+        set_balance(&mut api, TOKEN.clone(), Address::PublicKey(*ALICE), 100);
+        set_balance(
+            &mut api,
+            TOKEN.clone(),
+            Address::Contract(CONTRACT_NAME.to_string()),
+            50,
+        );
+        set_balance(&mut api, longer_token, Address::PublicKey(*BOB), 25);
+        let mut expected_balances = vec![
+            (ALICE.to_vec(), 100),
+            (CONTRACT_NAME.as_bytes().to_vec(), 50),
+        ];
+        expected_balances.sort();
+        assert_eq!(
+            iter_balance(&mut api, TOKEN.clone())
+                .unwrap()
+                .collect::<Vec<(Vec<u8>, u128)>>(),
+            expected_balances
+        );
+    }
 }
@@ -0,0 +1,92 @@
+use super::errors;
+use ellipticoin::{
+    helpers::{checked_sub, mul_div},
+    VersionedU128,
+};
+use serde::{Deserialize, Serialize};
+use wasm_rpc::error::Error;
+
+// Locked funds are held by the Token contract and vest linearly from
+// `start_block` to `end_block`. Nothing can be released before `cliff_block`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Lock {
+    pub amount: VersionedU128,
+    pub released: VersionedU128,
+    pub start_block: u32,
+    pub cliff_block: u32,
+    pub end_block: u32,
+}
+
+impl Lock {
+    pub fn new(
+        amount: u128,
+        start_block: u32,
+        cliff_block: u32,
+        end_block: u32,
+    ) -> Result<Self, Box<Error>> {
+        if amount == 0 || start_block > cliff_block || cliff_block > end_block {
+            return Err(Box::new(errors::INVALID_VESTING_SCHEDULE.clone()));
+        }
+        Ok(Self {
+            amount: amount.into(),
+            released: VersionedU128(0),
+            start_block,
+            cliff_block,
+            end_block,
+        })
+    }
+
+    pub fn vested(&self, block_number: u32) -> Result<u128, Box<Error>> {
+        if block_number < self.cliff_block {
+            Ok(0)
+        } else if block_number >= self.end_block {
+            Ok(self.amount.0)
+        } else {
+            mul_div(
+                self.amount.0,
+                (block_number - self.start_block) as u128,
+                (self.end_block - self.start_block) as u128,
+            )
+        }
+    }
+
+    pub fn releasable(&self, block_number: u32) -> Result<u128, Box<Error>> {
+        checked_sub(self.vested(block_number)?, self.released.0)
+    }
+
+    pub fn locked(&self) -> u128 {
+        self.amount.0 - self.released.0
+    }
+
+    pub fn is_fully_released(&self) -> bool {
+        self.released == self.amount
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vested() {
+        let lock = Lock::new(1000, 100, 150, 200).unwrap();
+        assert_eq!(lock.vested(0).unwrap(), 0);
+        assert_eq!(lock.vested(149).unwrap(), 0);
+        assert_eq!(lock.vested(150).unwrap(), 500);
+        assert_eq!(lock.vested(175).unwrap(), 750);
+        assert_eq!(lock.vested(200).unwrap(), 1000);
+        assert_eq!(lock.vested(300).unwrap(), 1000);
+    }
+
+    #[test]
+    fn test_invalid_schedule() {
+        assert_eq!(
+            Lock::new(1000, 150, 100, 200).unwrap_err().code,
+            errors::INVALID_VESTING_SCHEDULE.code
+        );
+        assert_eq!(
+            Lock::new(0, 100, 150, 200).unwrap_err().code,
+            errors::INVALID_VESTING_SCHEDULE.code
+        );
+    }
+}
@@ -1,10 +1,13 @@
 use crate::{
-    config::{network_id, verification_key},
+    config::{network_id, signing_key, verification_key},
     constants::TOKEN_CONTRACT,
+    helpers::sha256,
     models::transaction::next_nonce,
 };
+use ed25519_zebra::{SigningKey, VerificationKey};
 use serde::{Deserialize, Serialize};
 use serde_cbor::{from_slice, Value};
+use serde_cose::Sign1;
 use std::convert::TryInto;
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
@@ -31,16 +34,31 @@ impl Default for TransactionRequest {
 }
 
 impl TransactionRequest {
-    pub fn new(contract: String, function: &str, arguments: Vec<Value>) -> Self {
+    pub async fn new(contract: String, function: &str, arguments: Vec<Value>) -> Self {
         let transaction = Self {
             contract,
-            nonce: next_nonce(verification_key().to_vec()),
+            nonce: next_nonce(verification_key()).await,
             function: function.to_string(),
             arguments,
             ..Default::default()
         };
         transaction
     }
+
+    pub fn hash(&self) -> [u8; 32] {
+        sha256(serde_cbor::to_vec(self).unwrap())
+    }
+
+    pub fn sign(&self) -> Vec<u8> {
+        self.sign_with(signing_key())
+    }
+
+    pub fn sign_with(&self, signing_key: SigningKey) -> Vec<u8> {
+        let verification_key: [u8; 32] = VerificationKey::from(&signing_key).into();
+        let mut sign1 = Sign1::new(self, verification_key.to_vec());
+        sign1.sign(signing_key);
+        serde_cbor::to_vec(&sign1).unwrap()
+    }
 }
 
 impl From<crate::models::Transaction> for TransactionRequest {
@@ -55,3 +73,60 @@ impl From<crate::models::Transaction> for TransactionRequest {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::helpers::validate_transaction;
+    use ellipticoin_test_framework::constants::actors::{
+        ALICE, ALICES_PRIVATE_KEY, BOB, BOBS_PRIVATE_KEY,
+    };
+    use std::{convert::TryFrom, env};
+
+    fn transaction_request(sender: [u8; 32]) -> TransactionRequest {
+        TransactionRequest {
+            network_id: 0,
+            contract: "Token".to_string(),
+            sender,
+            nonce: 1,
+            function: "transfer".to_string(),
+            arguments: vec![],
+        }
+    }
+
+    fn signing_key(private_key: [u8; 64]) -> SigningKey {
+        SigningKey::from(<[u8; 32]>::try_from(&private_key[..32]).unwrap())
+    }
+
+    #[test]
+    fn test_sign() {
+        env::set_var("PRIVATE_KEY", base64::encode(&ALICES_PRIVATE_KEY[..]));
+        let transaction_request = transaction_request(*ALICE);
+        assert_eq!(
+            validate_transaction(&transaction_request.sign()).unwrap(),
+            transaction_request
+        );
+        assert!(validate_transaction(
+            &TransactionRequest {
+                sender: *BOB,
+                ..transaction_request
+            }
+            .sign()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_sign_with() {
+        let transaction_request = transaction_request(*BOB);
+        assert_eq!(
+            validate_transaction(&transaction_request.sign_with(signing_key(BOBS_PRIVATE_KEY)))
+                .unwrap(),
+            transaction_request
+        );
+        assert!(validate_transaction(
+            &transaction_request.sign_with(signing_key(ALICES_PRIVATE_KEY))
+        )
+        .is_err());
+    }
+}
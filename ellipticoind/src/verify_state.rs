@@ -0,0 +1,295 @@
+use crate::{
+    helpers::sha256,
+    start_up,
+    state::STATE,
+    system_contracts::{
+        api::InMemoryAPI,
+        ellipticoin, exchange,
+        exchange::constants::BASE_TOKEN,
+        token::{self, constants::ELC},
+        FromKey,
+    },
+};
+use ::ellipticoin::{Address, Token, VersionedU128};
+
+pub async fn verify_state() {
+    if STATE.lock().await.is_empty() {
+        start_up::load_genesis_state().await;
+        start_up::run_transactions_in_db().await;
+    }
+    let mut state = STATE.lock().await;
+    let mut api = InMemoryAPI::new(&mut state, None);
+    let violations = find_violations(&mut api);
+    for violation in &violations {
+        println!("{}", violation);
+    }
+    if violations.is_empty() {
+        println!("State is valid");
+    } else {
+        println!("Found {} violations", violations.len());
+        std::process::exit(1);
+    }
+}
+
+pub fn find_violations<API: ::ellipticoin::API>(api: &mut API) -> Vec<String> {
+    let mut violations = vec![];
+    violations.extend(total_supply_violations(api));
+    violations.extend(pool_violations(api));
+    violations.extend(issuance_rewards_violations(api));
+    violations
+}
+
+fn total_supply_violations<API: ::ellipticoin::API>(api: &mut API) -> Vec<String> {
+    let mut violations = vec![];
+    let total_supplies = match token::iter_total_supply(api) {
+        Ok(total_supplies) => total_supplies.collect::<Vec<_>>(),
+        Err(error) => return vec![decoding_violation("total supplies", error)],
+    };
+    for (token, total_supply) in total_supplies {
+        let balances = match api.scan_state::<_, VersionedU128>(
+            token::CONTRACT_NAME,
+            [vec![token::Namespace::Balance as u8], token.clone()].concat(),
+        ) {
+            Ok(balances) => balances,
+            Err(error) => {
+                violations.push(decoding_violation(
+                    &format!("balances of token {}", hex::encode(&token)),
+                    error,
+                ));
+                continue;
+            }
+        };
+        let sum_of_balances: u128 = balances
+            .into_iter()
+            .filter(|(address, _balance)| Address::from_key(address).is_some())
+            .map(|(_address, balance)| u128::from(balance))
+            .sum();
+        if sum_of_balances != total_supply {
+            violations.push(format!(
+                "Token {}: sum of balances {} doesn't equal total supply {}",
+                hex::encode(&token),
+                sum_of_balances,
+                total_supply
+            ));
+        }
+    }
+    violations
+}
+
+fn pool_violations<API: ::ellipticoin::API>(api: &mut API) -> Vec<String> {
+    let mut violations = vec![];
+    let pool_supplies_of_token = match exchange::iter_pool_supply_of_token(api) {
+        Ok(pool_supplies_of_token) => pool_supplies_of_token.collect::<Vec<_>>(),
+        Err(error) => return vec![decoding_violation("pool supplies of tokens", error)],
+    };
+    for (token, pool_supply_of_token) in pool_supplies_of_token {
+        let exchange_balance = raw_balance(api, &token, exchange::CONTRACT_NAME.as_bytes());
+        if exchange_balance != pool_supply_of_token {
+            violations.push(format!(
+                "Pool {}: pool supply of token {} doesn't equal the Exchange contract's balance {}",
+                hex::encode(&token),
+                pool_supply_of_token,
+                exchange_balance
+            ));
+        }
+        let liquidity_token = Token {
+            issuer: Address::Contract(exchange::CONTRACT_NAME.to_string()),
+            id: sha256(token.clone()).to_vec().into(),
+        };
+        let total_supply_of_liquidity_token = token::get_total_supply(api, liquidity_token);
+        if (total_supply_of_liquidity_token == 0) != (pool_supply_of_token == 0) {
+            violations.push(format!(
+                "Pool {}: liquidity token supply {} with pool supply of token {}",
+                hex::encode(&token),
+                total_supply_of_liquidity_token,
+                pool_supply_of_token
+            ));
+        }
+    }
+    let sum_of_pool_supplies_of_base_token: u128 =
+        match exchange::iter_pool_supply_of_base_token(api) {
+            Ok(pool_supplies_of_base_token) => pool_supplies_of_base_token
+                .map(|(_token, pool_supply_of_base_token)| pool_supply_of_base_token)
+                .sum(),
+            Err(error) => {
+                violations.push(decoding_violation("pool supplies of base token", error));
+                return violations;
+            }
+        };
+    let exchange_base_token_balance = token::get_balance(
+        api,
+        BASE_TOKEN.clone(),
+        Address::Contract(exchange::CONTRACT_NAME.to_string()),
+    );
+    if sum_of_pool_supplies_of_base_token != exchange_base_token_balance {
+        violations.push(format!(
+            "Sum of pool supplies of base token {} doesn't equal the Exchange contract's balance {}",
+            sum_of_pool_supplies_of_base_token, exchange_base_token_balance
+        ));
+    }
+    violations
+}
+
+fn issuance_rewards_violations<API: ::ellipticoin::API>(api: &mut API) -> Vec<String> {
+    let sum_of_issuance_rewards: u128 = match ellipticoin::iter_issuance_rewards(api) {
+        Ok(issuance_rewards) => issuance_rewards
+            .map(|(_address, issuance_rewards)| issuance_rewards)
+            .sum(),
+        Err(error) => return vec![decoding_violation("issuance rewards", error)],
+    };
+    let ellipticoin_balance = token::get_balance(
+        api,
+        ELC.clone(),
+        Address::Contract(ellipticoin::CONTRACT_NAME.to_string()),
+    );
+    if sum_of_issuance_rewards > ellipticoin_balance {
+        vec![format!(
+            "Issuance rewards {} exceed the Ellipticoin contract's ELC balance {}",
+            sum_of_issuance_rewards, ellipticoin_balance
+        )]
+    } else {
+        vec![]
+    }
+}
+
+fn raw_balance<API: ::ellipticoin::API>(api: &mut API, token: &[u8], address: &[u8]) -> u128 {
+    api.get_state::<_, VersionedU128>(
+        token::CONTRACT_NAME,
+        [
+            vec![token::Namespace::Balance as u8],
+            token.to_vec(),
+            address.to_vec(),
+        ]
+        .concat(),
+    )
+    .map(u128::from)
+    .unwrap_or(0)
+}
+
+fn decoding_violation(name: &str, error: serde_cbor::Error) -> String {
+    format!("Failed to decode {}: {}", name, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system_contracts::token::BASE_FACTOR;
+    use ::ellipticoin::helpers::db_key;
+    use ellipticoin_test_framework::{
+        constants::{
+            actors::{ALICE, BOB},
+            tokens::APPLES,
+        },
+        setup,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_valid_state() {
+        let mut state = HashMap::new();
+        let mut api = setup(hashmap! {}, &mut state);
+        token::mint(
+            &mut api,
+            APPLES.clone(),
+            Address::PublicKey(*ALICE),
+            100 * BASE_FACTOR,
+        )
+        .unwrap();
+        token::mint(
+            &mut api,
+            BASE_TOKEN.clone(),
+            Address::PublicKey(*ALICE),
+            100 * BASE_FACTOR,
+        )
+        .unwrap();
+        exchange::native::create_pool(&mut api, APPLES.clone(), 50 * BASE_FACTOR, BASE_FACTOR)
+            .unwrap();
+        assert!(find_violations(&mut api).is_empty());
+    }
+
+    #[test]
+    fn test_total_supply_violation() {
+        let mut state = HashMap::new();
+        let mut api = setup(hashmap! {}, &mut state);
+        token::mint(
+            &mut api,
+            APPLES.clone(),
+            Address::PublicKey(*ALICE),
+            100 * BASE_FACTOR,
+        )
+        .unwrap();
+        token::set_balance(&mut api, APPLES.clone(), Address::PublicKey(*BOB), 1);
+        assert_eq!(find_violations(&mut api).len(), 1);
+    }
+
+    #[test]
+    fn test_overlapping_token_ids() {
+        let mut state = HashMap::new();
+        let mut api = setup(hashmap! {}, &mut state);
+        let apples_2 = Token {
+            id: [APPLES.id.clone().into_vec(), vec![2]].concat().into(),
+            ..APPLES.clone()
+        };
+        token::mint(&mut api, APPLES.clone(), Address::PublicKey(*ALICE), 1).unwrap();
+        token::mint(&mut api, apples_2, Address::PublicKey(*ALICE), 2).unwrap();
+        assert!(find_violations(&mut api).is_empty());
+    }
+
+    #[test]
+    fn test_pool_violation() {
+        let mut state = HashMap::new();
+        let mut api = setup(hashmap! {}, &mut state);
+        token::mint(
+            &mut api,
+            APPLES.clone(),
+            Address::PublicKey(*ALICE),
+            100 * BASE_FACTOR,
+        )
+        .unwrap();
+        token::mint(
+            &mut api,
+            BASE_TOKEN.clone(),
+            Address::PublicKey(*ALICE),
+            100 * BASE_FACTOR,
+        )
+        .unwrap();
+        exchange::native::create_pool(&mut api, APPLES.clone(), 50 * BASE_FACTOR, BASE_FACTOR)
+            .unwrap();
+        exchange::set_pool_supply_of_token(&mut api, APPLES.clone(), 40 * BASE_FACTOR);
+        assert_eq!(find_violations(&mut api).len(), 1);
+    }
+
+    #[test]
+    fn test_undecodable_state_violation() {
+        let mut state = HashMap::new();
+        let mut api = setup(hashmap! {}, &mut state);
+        token::mint(
+            &mut api,
+            APPLES.clone(),
+            Address::PublicKey(*ALICE),
+            100 * BASE_FACTOR,
+        )
+        .unwrap();
+        ::ellipticoin::StateAPI::set(
+            &mut api,
+            &db_key(
+                token::CONTRACT_NAME,
+                &[
+                    vec![token::Namespace::Balance as u8],
+                    APPLES.clone().into(),
+                    Address::PublicKey(*BOB).into(),
+                ]
+                .concat(),
+            ),
+            &[0xff],
+        );
+        assert_eq!(
+            find_violations(&mut api),
+            vec![format!(
+                "Failed to decode balances of token {}: {}",
+                hex::encode::<Vec<u8>>(APPLES.clone().into()),
+                serde_cbor::from_slice::<VersionedU128>(&[0xff]).unwrap_err()
+            )]
+        );
+    }
+}
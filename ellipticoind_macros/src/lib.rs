@@ -29,6 +29,7 @@ struct Attribute {
     inputs: Punctuated<FnArg, Token![,]>,
     r_arrow_token: Token![->],
     output: Type,
+    storage: Option<(Token![as], Type)>,
 }
 
 impl Parse for AccessorArgs {
@@ -48,6 +49,11 @@ impl Parse for Attribute {
             inputs: content.parse_terminated(FnArg::parse).expect("expected fn"),
             r_arrow_token: input.parse()?,
             output: input.parse()?,
+            storage: if input.peek(Token![as]) {
+                Some((input.parse()?, input.parse()?))
+            } else {
+                None
+            },
         })
     }
 }
@@ -60,6 +66,7 @@ pub fn state_accessors(item: TokenStream) -> TokenStream {
         Ident,
         syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
         Type,
+        Type,
     )> = attrs
         .iter()
         .cloned()
@@ -68,15 +75,23 @@ pub fn state_accessors(item: TokenStream) -> TokenStream {
                  ident,
                  output,
                  inputs,
+                 storage,
                  ..
-             }| { (ident, inputs, output) },
+             }| {
+                // Values are stored as `storage` when it's given and
+                // converted to and from `output` with `From`
+                let storage = storage
+                    .map(|(_, storage)| storage)
+                    .unwrap_or(output.clone());
+                (ident, inputs, output, storage)
+            },
         )
         .collect();
     let get_fn = Ident::new(&"get_state", Span::call_site());
     let base_namespace = Ident::new("Namespace", Span::call_site());
     let getters = attrs2
         .iter()
-        .map(|(ident, inputs, ty)| {
+        .map(|(ident, inputs, ty, storage)| {
             let getter_name = Ident::new(&format!("get_{}", &ident), Span::call_site());
             let namespace = Ident::new(&ident.to_string().to_camel_case(), Span::call_site());
             let mut p: Punctuated<syn::Expr, syn::token::Comma> = Punctuated::new();
@@ -92,7 +107,7 @@ pub fn state_accessors(item: TokenStream) -> TokenStream {
             inputs3.insert(0, fn_arg);
             parse_quote!(
             pub fn #getter_name<API: ellipticoin::API>(#inputs3) -> #ty {
-                api.#get_fn(
+                api.#get_fn::<_, #storage>(
                     CONTRACT_NAME,
                     [
                         [#base_namespace::#namespace as u8].to_vec(),
@@ -100,6 +115,7 @@ pub fn state_accessors(item: TokenStream) -> TokenStream {
                     ]
                     .concat(),
                 )
+                .map(<#ty as From<#storage>>::from)
                 .unwrap_or(Default::default())
             })
         })
@@ -107,7 +123,7 @@ pub fn state_accessors(item: TokenStream) -> TokenStream {
     let set_fn = Ident::new(&"set_state", Span::call_site());
     let setters = attrs2
         .iter()
-        .map(|(ident, inputs, ty)| {
+        .map(|(ident, inputs, ty, storage)| {
             let setter_name = Ident::new(&format!("set_{}", &ident), Span::call_site());
             let namespace = Ident::new(&ident.to_string().to_camel_case(), Span::call_site());
             let mut p: Punctuated<syn::Expr, syn::token::Comma> = Punctuated::new();
@@ -127,14 +143,62 @@ pub fn state_accessors(item: TokenStream) -> TokenStream {
                             CONTRACT_NAME,
             [[#base_namespace::#namespace as u8].to_vec(),
                                     #p
-            ].concat(), value);
+            ].concat(), <#storage as From<#ty>>::from(value));
                         })
         })
         .collect::<Vec<syn::ItemFn>>();
+    let scan_fn = Ident::new("scan_state", Span::call_site());
+    let iterators = attrs2
+        .iter()
+        .filter(|(_ident, inputs, _ty, _storage)| !inputs.is_empty())
+        .map(|(ident, inputs, ty, storage)| {
+            let iterator_name = Ident::new(&format!("iter_{}", &ident), Span::call_site());
+            let namespace = Ident::new(&ident.to_string().to_camel_case(), Span::call_site());
+            let mut prefix_inputs = inputs.clone();
+            let key_filter = match prefix_inputs.pop().map(|pair| pair.into_value()) {
+                // Key parts aren't delimited so keys under another prefix
+                // that starts with this one are skipped by decoding the rest
+                Some(syn::FnArg::Typed(syn::PatType { ty: key_ty, .. }))
+                    if !prefix_inputs.is_empty() =>
+                {
+                    quote!(.filter(|(key, _value)| {
+                        <#key_ty as crate::system_contracts::FromKey>::from_key(key).is_some()
+                    }))
+                }
+                _ => quote!(),
+            };
+            let mut p: Punctuated<syn::Expr, syn::token::Comma> = Punctuated::new();
+            for input in prefix_inputs.iter() {
+                if let syn::FnArg::Typed(syn::PatType { pat, .. }) = input {
+                    p.push(parse_quote!(#pat.into()))
+                } else {
+                    panic!("oh no")
+                }
+            }
+            let fn_arg: FnArg = parse_quote!(api: &mut API);
+            prefix_inputs.insert(0, fn_arg);
+            parse_quote!(
+            pub fn #iterator_name<API: ellipticoin::API>(
+                #prefix_inputs
+            ) -> Result<impl Iterator<Item = (Vec<u8>, #ty)>, ellipticoin::serde_cbor::Error> {
+                Ok(api.#scan_fn::<_, #storage>(
+                    CONTRACT_NAME,
+                    [
+                        [#base_namespace::#namespace as u8].to_vec(),
+                        #p
+                    ]
+                    .concat(),
+                )?
+                .into_iter()
+                #key_filter
+                .map(|(key, value)| (key, <#ty as From<#storage>>::from(value))))
+            })
+        })
+        .collect::<Vec<syn::ItemFn>>();
     let mut namespace: ItemEnum = parse_quote!(
         pub enum #base_namespace {}
     );
-    for (ident, _inputs, _ty) in attrs2.iter() {
+    for (ident, _inputs, _ty, _storage) in attrs2.iter() {
         let varient = Variant {
             attrs: vec![],
             ident: Ident::new(&ident.to_string().to_camel_case(), Span::call_site()),
@@ -143,10 +207,16 @@ pub fn state_accessors(item: TokenStream) -> TokenStream {
         };
         namespace.variants.push(varient);
     }
+    let namespace_names = attrs2
+        .iter()
+        .map(|(ident, _inputs, _ty, _storage)| ident.to_string())
+        .collect::<Vec<String>>();
     (quote! {
         #namespace
+        pub const NAMESPACES: &[&str] = &[#(#namespace_names),*];
         #(#getters)*
         #(#setters)*
+        #(#iterators)*
     })
     .into()
 }
@@ -1,4 +1,4 @@
-use crate::types::Address;
+use crate::types::{Address, Event};
 use helpers::db_key;
 pub use wasm_rpc::{pointer, serde_cbor};
 use wasm_rpc::{
@@ -10,12 +10,32 @@ pub use wasm_rpc_macros::{export, export_native};
 pub trait StateAPI {
     fn get(&mut self, key: &[u8]) -> Vec<u8>;
     fn set(&mut self, key: &[u8], value: &[u8]);
+    fn scan(&mut self, prefix: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)>;
     fn commit(&mut self);
     fn revert(&mut self);
+    fn checkpoint(&mut self);
+    fn rollback(&mut self);
+    fn release(&mut self);
 }
 
 pub trait API: StateAPI {
     fn caller(&self) -> Address;
+    fn emit_event(&mut self, event: Event);
+
+    fn emit<D: Serialize>(
+        &mut self,
+        contract: &'static str,
+        name: &'static str,
+        addresses: Vec<Address>,
+        data: D,
+    ) {
+        self.emit_event(Event {
+            contract: contract.to_string(),
+            name: name.to_string(),
+            addresses,
+            data: to_vec(&data).unwrap(),
+        })
+    }
 
     fn commit(&mut self) {
         StateAPI::commit(self);
@@ -25,6 +45,17 @@ pub trait API: StateAPI {
         StateAPI::revert(self);
     }
 
+    fn atomically<T, E, F: FnOnce(&mut Self) -> Result<T, E>>(&mut self, f: F) -> Result<T, E> {
+        StateAPI::checkpoint(self);
+        let result = f(self);
+        if result.is_ok() {
+            StateAPI::release(self);
+        } else {
+            StateAPI::rollback(self);
+        }
+        result
+    }
+
     fn get_state<K: Into<Vec<u8>>, V: DeserializeOwned>(
         &mut self,
         contract: &'static str,
@@ -33,6 +64,18 @@ pub trait API: StateAPI {
         from_slice(&StateAPI::get(self, &db_key(&contract, &key.into())))
     }
 
+    fn scan_state<K: Into<Vec<u8>>, V: DeserializeOwned>(
+        &mut self,
+        contract: &'static str,
+        prefix: K,
+    ) -> Result<Vec<(Vec<u8>, V)>, serde_cbor::Error> {
+        let prefix = db_key(&contract, &prefix.into());
+        StateAPI::scan(self, &prefix)
+            .into_iter()
+            .map(|(key, value)| Ok((key[prefix.len()..].to_vec(), from_slice(&value)?)))
+            .collect()
+    }
+
     fn set_state<K: Into<Vec<u8>>, V: Serialize>(
         &mut self,
 
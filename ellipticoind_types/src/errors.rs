@@ -5,4 +5,8 @@ lazy_static! {
         code: 4,
         message: "Addresses must be 32 bytes long".to_string(),
     };
+    pub static ref ARITHMETIC_OVERFLOW: Error = Error {
+        code: 5,
+        message: "Arithmetic overflow".to_string(),
+    };
 }
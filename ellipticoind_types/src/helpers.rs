@@ -1,3 +1,4 @@
+use errors::{Error, ARITHMETIC_OVERFLOW};
 use sha2::{Digest, Sha256};
 
 pub fn zero_pad_vec(vec: &[u8], len: usize) -> Vec<u8> {
@@ -15,3 +16,19 @@ pub fn sha256(message: Vec<u8>) -> [u8; 32] {
 pub fn db_key(contract_address: &'static str, key: &[u8]) -> Vec<u8> {
     [&sha256(contract_address.as_bytes().to_vec())[..], key].concat()
 }
+
+pub fn checked_add(a: u128, b: u128) -> Result<u128, Box<Error>> {
+    a.checked_add(b)
+        .ok_or_else(|| Box::new(ARITHMETIC_OVERFLOW.clone()))
+}
+
+pub fn checked_sub(a: u128, b: u128) -> Result<u128, Box<Error>> {
+    a.checked_sub(b)
+        .ok_or_else(|| Box::new(ARITHMETIC_OVERFLOW.clone()))
+}
+
+pub fn mul_div(a: u128, b: u128, c: u128) -> Result<u128, Box<Error>> {
+    a.checked_mul(b)
+        .and_then(|product| product.checked_div(c))
+        .ok_or_else(|| Box::new(ARITHMETIC_OVERFLOW.clone()))
+}
@@ -12,7 +12,10 @@ pub mod bytes;
 pub mod errors;
 pub mod helpers;
 pub mod macros;
+pub mod merkle;
 pub mod types;
+pub mod versioned_u128;
 pub use api::*;
 pub use bytes::Bytes;
 pub use types::*;
+pub use versioned_u128::VersionedU128;
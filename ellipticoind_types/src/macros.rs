@@ -1,7 +1,7 @@
 #[macro_export]
 macro_rules! pay {
     ($api:expr, $token:expr, $recipient:expr, $amount:expr) => {
-        token::transfer_from(
+        token::transfer(
             $api,
             $token,
             Address::Contract(CONTRACT_NAME.to_string()),
@@ -14,7 +14,7 @@ macro_rules! pay {
 #[macro_export]
 macro_rules! charge {
     ($api:expr, $token:expr, $recipient:expr, $amount:expr) => {
-        token::transfer_from(
+        token::transfer(
             $api,
             $token,
             $recipient,
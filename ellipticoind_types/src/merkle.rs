@@ -0,0 +1,297 @@
+use helpers::sha256;
+use std::sync::Arc;
+use wasm_rpc::serde::{Deserialize, Serialize};
+
+pub type Hash = [u8; 32];
+pub const EMPTY_HASH: Hash = [0; 32];
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+#[derive(Clone, Deserialize, Serialize, PartialEq, Debug)]
+pub struct Proof {
+    pub leaf: Option<(Hash, Hash)>,
+    pub siblings: Vec<Hash>,
+}
+
+pub fn hash_leaf(key_hash: &Hash, value_hash: &Hash) -> Hash {
+    sha256([&[LEAF_PREFIX][..], key_hash, value_hash].concat())
+}
+
+pub fn hash_node(left: &Hash, right: &Hash) -> Hash {
+    sha256([&[NODE_PREFIX][..], left, right].concat())
+}
+
+fn bit(hash: &Hash, depth: usize) -> bool {
+    hash[depth / 8] & (0x80 >> (depth % 8)) != 0
+}
+
+// Nodes are shared between copies of a tree so updating a copy only
+// rebuilds the path from the root to the updated leaf.
+#[derive(Debug)]
+enum Node {
+    Empty,
+    Leaf(Hash, Hash),
+    Branch(Hash, Arc<Node>, Arc<Node>),
+}
+
+impl Node {
+    fn hash(&self) -> Hash {
+        match self {
+            Node::Empty => EMPTY_HASH,
+            Node::Leaf(key_hash, value_hash) => hash_leaf(key_hash, value_hash),
+            Node::Branch(hash, _, _) => *hash,
+        }
+    }
+
+    fn branch(left: Arc<Node>, right: Arc<Node>) -> Arc<Node> {
+        Arc::new(Node::Branch(
+            hash_node(&left.hash(), &right.hash()),
+            left,
+            right,
+        ))
+    }
+}
+
+fn insert(node: &Arc<Node>, depth: usize, key_hash: Hash, value_hash: Hash) -> Arc<Node> {
+    match **node {
+        Node::Empty => Arc::new(Node::Leaf(key_hash, value_hash)),
+        Node::Leaf(leaf_key_hash, _) if leaf_key_hash == key_hash => {
+            Arc::new(Node::Leaf(key_hash, value_hash))
+        }
+        Node::Leaf(leaf_key_hash, _) => {
+            let empty = Arc::new(Node::Empty);
+            let branch = if bit(&leaf_key_hash, depth) {
+                Node::branch(empty, node.clone())
+            } else {
+                Node::branch(node.clone(), empty)
+            };
+            insert(&branch, depth, key_hash, value_hash)
+        }
+        Node::Branch(_, ref left, ref right) => {
+            if bit(&key_hash, depth) {
+                Node::branch(left.clone(), insert(right, depth + 1, key_hash, value_hash))
+            } else {
+                Node::branch(insert(left, depth + 1, key_hash, value_hash), right.clone())
+            }
+        }
+    }
+}
+
+fn remove(node: &Arc<Node>, depth: usize, key_hash: &Hash) -> Arc<Node> {
+    match **node {
+        Node::Leaf(leaf_key_hash, _) if &leaf_key_hash == key_hash => Arc::new(Node::Empty),
+        Node::Branch(_, ref left, ref right) => {
+            let (left, right) = if bit(key_hash, depth) {
+                (left.clone(), remove(right, depth + 1, key_hash))
+            } else {
+                (remove(left, depth + 1, key_hash), right.clone())
+            };
+            // A subtree with a single leaf hashes to that leaf
+            match (&*left, &*right) {
+                (Node::Empty, Node::Empty) | (Node::Leaf(..), Node::Empty) => left,
+                (Node::Empty, Node::Leaf(..)) => right,
+                _ => Node::branch(left, right),
+            }
+        }
+        _ => node.clone(),
+    }
+}
+
+// A sparse Merkle tree over `(sha256(key), sha256(value))` leaves.
+// Subtrees with a single leaf are stored as that leaf.
+#[derive(Clone, Debug)]
+pub struct Tree {
+    root: Arc<Node>,
+}
+
+impl Default for Tree {
+    fn default() -> Self {
+        Self {
+            root: Arc::new(Node::Empty),
+        }
+    }
+}
+
+impl Tree {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn root(&self) -> Hash {
+        self.root.hash()
+    }
+
+    pub fn insert(&mut self, key_hash: Hash, value_hash: Hash) {
+        self.root = insert(&self.root, 0, key_hash, value_hash);
+    }
+
+    pub fn remove(&mut self, key_hash: &Hash) {
+        self.root = remove(&self.root, 0, key_hash);
+    }
+
+    pub fn prove(&self, key_hash: &Hash) -> Proof {
+        let mut node = &self.root;
+        let mut siblings = vec![];
+        loop {
+            match **node {
+                Node::Branch(_, ref left, ref right) => {
+                    if bit(key_hash, siblings.len()) {
+                        siblings.push(left.hash());
+                        node = right;
+                    } else {
+                        siblings.push(right.hash());
+                        node = left;
+                    }
+                }
+                Node::Leaf(key_hash, value_hash) => {
+                    return Proof {
+                        leaf: Some((key_hash, value_hash)),
+                        siblings,
+                    }
+                }
+                Node::Empty => {
+                    return Proof {
+                        leaf: None,
+                        siblings,
+                    }
+                }
+            }
+        }
+    }
+}
+
+// An empty `value` verifies that `key` is not included in the tree.
+pub fn verify(root: &Hash, key: &[u8], value: &[u8], proof: &Proof) -> bool {
+    if proof.siblings.len() > 256 {
+        return false;
+    }
+    let key_hash = sha256(key.to_vec());
+    let mut hash = match proof.leaf {
+        Some((leaf_key_hash, leaf_value_hash)) => {
+            if value.is_empty() {
+                if leaf_key_hash == key_hash
+                    || (0..proof.siblings.len())
+                        .any(|depth| bit(&leaf_key_hash, depth) != bit(&key_hash, depth))
+                {
+                    return false;
+                }
+            } else if leaf_key_hash != key_hash || leaf_value_hash != sha256(value.to_vec()) {
+                return false;
+            }
+            hash_leaf(&leaf_key_hash, &leaf_value_hash)
+        }
+        None => {
+            if !value.is_empty() {
+                return false;
+            }
+            EMPTY_HASH
+        }
+    };
+    for (depth, sibling) in proof.siblings.iter().enumerate().rev() {
+        hash = if bit(&key_hash, depth) {
+            hash_node(sibling, &hash)
+        } else {
+            hash_node(&hash, sibling)
+        };
+    }
+    &hash == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(key: &[u8], value: &[u8]) -> (Hash, Hash) {
+        (sha256(key.to_vec()), sha256(value.to_vec()))
+    }
+
+    fn tree(entries: &[(&[u8], &[u8])]) -> Tree {
+        let mut tree = Tree::new();
+        for (key, value) in entries {
+            let (key_hash, value_hash) = leaf(key, value);
+            tree.insert(key_hash, value_hash);
+        }
+        tree
+    }
+
+    #[test]
+    fn test_root() {
+        assert_eq!(Tree::new().root(), EMPTY_HASH);
+        let (key_hash, value_hash) = leaf(&[1], &[2]);
+        assert_eq!(
+            tree(&[(&[1], &[2])]).root(),
+            hash_leaf(&key_hash, &value_hash)
+        );
+    }
+
+    #[test]
+    fn test_root_is_independent_of_insertion_order() {
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = (0..50u8).map(|i| (vec![i], vec![i, i])).collect();
+        let entries: Vec<(&[u8], &[u8])> = entries
+            .iter()
+            .map(|(key, value)| (&key[..], &value[..]))
+            .collect();
+        let reversed: Vec<(&[u8], &[u8])> = entries.iter().cloned().rev().collect();
+        assert_eq!(tree(&entries).root(), tree(&reversed).root());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut tree = tree(&[(&[1], &[1]), (&[2], &[2])]);
+        let (key_hash, value_hash) = leaf(&[3], &[3]);
+        let root = tree.root();
+        tree.insert(key_hash, value_hash);
+        assert_ne!(tree.root(), root);
+        tree.remove(&key_hash);
+        assert_eq!(tree.root(), root);
+        tree.remove(&sha256(vec![1]));
+        tree.remove(&sha256(vec![2]));
+        assert_eq!(tree.root(), EMPTY_HASH);
+    }
+
+    #[test]
+    fn test_clones_are_independent() {
+        let mut tree = tree(&[(&[1], &[1]), (&[2], &[2])]);
+        let root = tree.root();
+        let clone = tree.clone();
+        let (key_hash, value_hash) = leaf(&[1], &[3]);
+        tree.insert(key_hash, value_hash);
+        assert_eq!(clone.root(), root);
+        assert_ne!(tree.root(), root);
+    }
+
+    #[test]
+    fn test_verify_inclusion() {
+        let tree = tree(&[(&[1], &[1]), (&[2], &[2]), (&[3], &[3])]);
+        let proof = tree.prove(&sha256(vec![2]));
+        assert!(verify(&tree.root(), &[2], &[2], &proof));
+        assert!(!verify(&tree.root(), &[2], &[3], &proof));
+        assert!(!verify(&tree.root(), &[2], &[], &proof));
+        assert!(!verify(&tree.root(), &[1], &[2], &proof));
+        assert!(!verify(&EMPTY_HASH, &[2], &[2], &proof));
+    }
+
+    #[test]
+    fn test_verify_exclusion() {
+        let tree = tree(&[(&[1], &[1]), (&[2], &[2]), (&[3], &[3])]);
+        let proof = tree.prove(&sha256(vec![4]));
+        assert!(verify(&tree.root(), &[4], &[], &proof));
+        assert!(!verify(&tree.root(), &[4], &[4], &proof));
+        let proof = Tree::new().prove(&sha256(vec![4]));
+        assert!(verify(&EMPTY_HASH, &[4], &[], &proof));
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_proofs() {
+        let tree = tree(&[(&[1], &[1]), (&[2], &[2]), (&[3], &[3])]);
+        let mut proof = tree.prove(&sha256(vec![2]));
+        proof.siblings[0] = EMPTY_HASH;
+        assert!(!verify(&tree.root(), &[2], &[2], &proof));
+        let mut proof = tree.prove(&sha256(vec![2]));
+        proof.siblings = vec![EMPTY_HASH; 257];
+        assert!(!verify(&tree.root(), &[2], &[2], &proof));
+        let mut proof = tree.prove(&sha256(vec![4]));
+        proof.leaf = Some(leaf(&[4], &[4]));
+        assert!(!verify(&tree.root(), &[4], &[], &proof));
+    }
+}
@@ -17,6 +17,14 @@ impl Into<Vec<u8>> for Token {
     }
 }
 
+#[derive(Clone, Deserialize, Serialize, PartialEq, Debug)]
+pub struct Event {
+    pub contract: String,
+    pub name: String,
+    pub addresses: Vec<Address>,
+    pub data: Vec<u8>,
+}
+
 #[derive(Clone, Hash, Deserialize, Serialize, PartialEq, Eq, Debug)]
 pub enum Address {
     PublicKey([u8; 32]),
@@ -0,0 +1,84 @@
+use core::{
+    convert::TryInto,
+    fmt::{self},
+};
+use wasm_rpc::serde::{
+    de::{self, Deserializer, Visitor},
+    ser::Serializer,
+    Deserialize, Serialize,
+};
+
+// Amounts were originally stored as CBOR encoded u64s. CBOR integers can't
+// hold anything larger than a u64 so those values are stored as 16 big endian
+// bytes instead. Values that fit in a u64 keep the original encoding which
+// means existing state decodes (and hashes) the same as before.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VersionedU128(pub u128);
+
+struct VersionedU128Visitor;
+
+impl Serialize for VersionedU128 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.0 <= u64::MAX as u128 {
+            serializer.serialize_u64(self.0 as u64)
+        } else {
+            serializer.serialize_bytes(&self.0.to_be_bytes())
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for VersionedU128Visitor {
+    type Value = VersionedU128;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an integer or 16 bytes")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<VersionedU128, E>
+    where
+        E: de::Error,
+    {
+        Ok(VersionedU128(v as u128))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<VersionedU128, E>
+    where
+        E: de::Error,
+    {
+        let bytes = v
+            .try_into()
+            .map_err(|_| E::invalid_length(v.len(), &self))?;
+        Ok(VersionedU128(u128::from_be_bytes(bytes)))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<VersionedU128, E>
+    where
+        E: de::Error,
+    {
+        self.visit_bytes(&v)
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionedU128 {
+    fn deserialize<D>(deserializer: D) -> Result<VersionedU128, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(VersionedU128Visitor)
+    }
+}
+
+impl From<u128> for VersionedU128 {
+    fn from(n: u128) -> Self {
+        VersionedU128(n)
+    }
+}
+
+impl From<VersionedU128> for u128 {
+    fn from(n: VersionedU128) -> Self {
+        n.0
+    }
+}
@@ -0,0 +1,8 @@
+CREATE TABLE "state_versions" (
+  "block_number" INTEGER NOT NULL,
+  "key" BYTEA NOT NULL,
+  "previous_value" BYTEA NOT NULL,
+  "value" BYTEA NOT NULL,
+  PRIMARY KEY ("block_number", "key")
+);
+CREATE INDEX "state_versions_key_block_number" ON "state_versions" ("key", "block_number");
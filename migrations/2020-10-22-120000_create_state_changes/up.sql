@@ -0,0 +1,8 @@
+CREATE TABLE "state_changes" (
+  "id" SERIAL PRIMARY KEY,
+  "transaction_id" INTEGER REFERENCES "transactions" ON DELETE CASCADE NOT NULL,
+  "key" BYTEA NOT NULL,
+  "old_value" BYTEA NOT NULL,
+  "new_value" BYTEA NOT NULL
+);
+CREATE INDEX "state_changes_transaction_id" ON "state_changes" ("transaction_id");
@@ -0,0 +1,2 @@
+DROP INDEX "transactions_hash";
+ALTER TABLE "transactions" DROP COLUMN "hash";
@@ -0,0 +1,2 @@
+ALTER TABLE "transactions" ADD COLUMN "hash" BYTEA;
+CREATE INDEX "transactions_hash" ON "transactions" ("hash");
@@ -0,0 +1,11 @@
+CREATE TABLE "events" (
+  "id" SERIAL PRIMARY KEY,
+  "transaction_id" INTEGER REFERENCES "transactions" ON DELETE CASCADE NOT NULL,
+  "contract" VARCHAR NOT NULL,
+  "name" VARCHAR NOT NULL,
+  "addresses" BYTEA[] NOT NULL,
+  "data" BYTEA NOT NULL
+);
+CREATE INDEX "events_transaction_id" ON "events" ("transaction_id");
+CREATE INDEX "events_contract_name" ON "events" ("contract", "name");
+CREATE INDEX "events_addresses" ON "events" USING GIN ("addresses");